@@ -1,8 +1,9 @@
-// TradingView Bridge v1.6.0 - Works on both TradingView.com and app.hyperliquid.xyz
+// TradingView Bridge v1.8.0 - Works on both TradingView.com and app.hyperliquid.xyz
 console.log('[TV Bridge] Script starting...');
 
-const BRIDGE_PORT = 3456;
-const BRIDGE_URL = `http://localhost:${BRIDGE_PORT}`;
+const DEFAULT_BRIDGE_PORT = 3456;
+// The app falls back to the next few ports when 3456 is taken
+const BRIDGE_FALLBACK_PORTS = 10;
 const IS_HYPERLIQUID = window.location.hostname === 'app.hyperliquid.xyz';
 const IS_IN_IFRAME = window !== window.top;
 
@@ -25,6 +26,93 @@ let lastOverlayPosition = null; // Remember position for minimize/maximize
 // Settings (fetched from app, with fallback defaults)
 let settings = { risk: 1.00, leverage: 25, useMarketPrice: true, asset: 'BTC', price: 0 };
 
+// =====================================================
+// PAIRING & PORT DISCOVERY
+// =====================================================
+
+// Pairing code shown in the app (Settings > TradingView Bridge), sent as a bearer token.
+// Kept in extension storage rather than localStorage so TradingView's own scripts can't read it.
+// The port the app was last found on is kept alongside it.
+const BRIDGE_TOKEN_KEY = 'bridgeToken';
+const BRIDGE_PORT_KEY = 'bridgePort';
+let bridgeToken = '';
+let bridgePort = DEFAULT_BRIDGE_PORT;
+let pairingPromptShown = false;
+let portPromptShown = false;
+let portDiscovery = null;
+
+const bridgeTokenLoaded = new Promise((resolve) => {
+  chrome.storage.local.get([BRIDGE_TOKEN_KEY, BRIDGE_PORT_KEY], (items) => {
+    bridgeToken = (items && items[BRIDGE_TOKEN_KEY]) || '';
+    bridgePort = (items && items[BRIDGE_PORT_KEY]) || DEFAULT_BRIDGE_PORT;
+    resolve();
+  });
+});
+
+function bridgeUrl(port = bridgePort) {
+  return `http://localhost:${port}`;
+}
+
+// Identify the app through its public schema route, without the pairing code: whatever else
+// might be listening on these ports must never be sent the token
+async function isBridgeAt(port) {
+  try {
+    const response = await fetch(`${bridgeUrl(port)}/v1/schema/response-success`, { credentials: 'omit' });
+    if (!response.ok) return false;
+    const schema = await response.json();
+    return typeof schema.$id === 'string' && schema.$id.startsWith('hyperliquid-trader/bridge/');
+  } catch (e) {
+    return false;
+  }
+}
+
+// Probe the app's fallback range; when interactive, ask for the port as a last resort
+async function discoverBridgePort(interactive) {
+  for (let port = DEFAULT_BRIDGE_PORT; port <= DEFAULT_BRIDGE_PORT + BRIDGE_FALLBACK_PORTS; port++) {
+    if (port !== bridgePort && await isBridgeAt(port)) return port;
+  }
+  if (!interactive || portPromptShown) return null;
+
+  portPromptShown = true;
+  const answer = window.prompt(`TradingView Bridge: Hyperliquid Trader is not answering on ports ${DEFAULT_BRIDGE_PORT}-${DEFAULT_BRIDGE_PORT + BRIDGE_FALLBACK_PORTS}. Enter the bridge port shown in the app (Settings > TradingView Bridge)`);
+  const port = parseInt(answer, 10);
+  if (port >= 1024 && port <= 65535 && await isBridgeAt(port)) return port;
+  return null;
+}
+
+// Background calls only probe; user actions (interactive) may also prompt for the port.
+// pair: false skips the pairing prompt, for calls made without any user involvement.
+async function bridgeFetch(path, options = {}, { interactive = false, pair = true } = {}) {
+  await bridgeTokenLoaded;
+  const send = () => fetch(`${bridgeUrl()}${path}`, {
+    ...options,
+    headers: { ...(options.headers || {}), Authorization: `Bearer ${bridgeToken}` }
+  });
+
+  let response;
+  try {
+    response = await send();
+  } catch (e) {
+    // Nothing on the saved port: the app is closed or bound another one
+    portDiscovery = portDiscovery || discoverBridgePort(interactive).finally(() => { portDiscovery = null; });
+    const port = await portDiscovery;
+    if (!port) throw e;
+    console.log('[TV Bridge] Found the app on port', port);
+    bridgePort = port;
+    chrome.storage.local.set({ [BRIDGE_PORT_KEY]: port });
+    response = await send();
+  }
+  if (response.status !== 401 || !pair || pairingPromptShown) return response;
+
+  // Ask once per page load so background polling can't spam prompts
+  pairingPromptShown = true;
+  const code = window.prompt('TradingView Bridge: enter the pairing code shown in Hyperliquid Trader (Settings > TradingView Bridge)');
+  if (!code || !code.trim()) return response;
+  bridgeToken = code.trim();
+  chrome.storage.local.set({ [BRIDGE_TOKEN_KEY]: bridgeToken });
+  return send();
+}
+
 // =====================================================
 // IFRAME COMMUNICATION (for Hyperliquid)
 // =====================================================
@@ -62,7 +150,7 @@ function sendToParent(type, data) {
 
 async function fetchSettings() {
   try {
-    const response = await fetch(`${BRIDGE_URL}/settings`);
+    const response = await bridgeFetch('/settings');
     if (response.ok) {
       const data = await response.json();
       settings.risk = data.risk || 1;
@@ -75,7 +163,7 @@ async function fetchSettings() {
 
 async function fetchCurrentPrice() {
   try {
-    const response = await fetch(`${BRIDGE_URL}/settings`);
+    const response = await bridgeFetch('/settings');
     if (response.ok) {
       const data = await response.json();
       if (data.price > 0) {
@@ -87,6 +175,53 @@ async function fetchCurrentPrice() {
   return null;
 }
 
+// =====================================================
+// APP PRESENCE (hello / heartbeat)
+// =====================================================
+
+const EXTENSION_VERSION = chrome.runtime.getManifest().version;
+const DEFAULT_HEARTBEAT_SECS = 5;
+// While the app is closed or the extension isn't paired yet
+const PRESENCE_RETRY_SECS = 30;
+
+// Say hello on load, then heartbeat at the interval the app asks for, so it can show whether
+// the extension is connected. After a restart the app asks for the open position tool again.
+async function sendPresence(path) {
+  let next = '/heartbeat';
+  let delaySecs = DEFAULT_HEARTBEAT_SECS;
+  try {
+    const response = await bridgeFetch(path, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ version: EXTENSION_VERSION })
+    }, { pair: false });
+    if (response.ok) {
+      const data = await response.json();
+      delaySecs = data.heartbeatIntervalSecs || DEFAULT_HEARTBEAT_SECS;
+      if (data.resyncPositions) resendPosition();
+    } else {
+      next = '/hello';
+      delaySecs = PRESENCE_RETRY_SECS;
+    }
+  } catch (e) {
+    next = '/hello';
+    delaySecs = PRESENCE_RETRY_SECS;
+  }
+  setTimeout(() => sendPresence(next), delaySecs * 1000);
+}
+
+// The overlay only holds data while the position tool is open on the chart, so it is current
+async function resendPosition() {
+  if (!currentPositionData) return;
+  try {
+    await bridgeFetch('/position', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ ...currentPositionData, timestamp: Date.now() })
+    }, { pair: false });
+  } catch (e) {}
+}
+
 // =====================================================
 // STYLES
 // =====================================================
@@ -714,7 +849,7 @@ function createOverlay(data) {
     enterBtn.innerHTML = `<div class="tv-bridge-spinner"></div> Executing...`;
 
     try {
-      const response = await fetch(`${BRIDGE_URL}/execute-trade`, {
+      const response = await bridgeFetch('/execute-trade', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({
@@ -725,7 +860,7 @@ function createOverlay(data) {
           risk: risk,
           leverage: leverage
         })
-      });
+      }, { interactive: true });
 
       const result = await response.json();
 
@@ -1058,6 +1193,11 @@ try {
   }
 } catch (e) {}
 
+// Only the top frame talks to the app
+if (!IS_IN_IFRAME) {
+  sendPresence('/hello');
+}
+
 // =====================================================
 // HYPERLIQUID FULLSCREEN CHART (Shift+G)
 // Makes chart fullscreen while keeping drawing tools
@@ -1,7 +1,11 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::thread;
 use std::sync::{Arc, Mutex};
-use tauri::Emitter;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
 use reqwest;
 
 #[cfg(target_os = "macos")]
@@ -11,6 +15,23 @@ use security_framework::passwords::{set_generic_password, get_generic_password,
 const SERVICE_NAME: &str = "com.hyperliquid.trader";
 const ACCOUNT_NAME: &str = "vault_password";
 const BRIDGE_PORT: u16 = 3456;
+const BRIDGE_API_VERSION: &str = "1";
+const DEFAULT_TRADE_TIMEOUT_SECS: u64 = 60;
+const MAX_COUNTDOWN_SECS: u64 = 30;
+// Part of the trade timeout always left for the frontend's result after the countdown
+const MIN_TRADE_RESULT_WAIT_SECS: u64 = 5;
+const MAX_RECENT_ERRORS: usize = 50;
+const MAX_BRIDGE_LOG_ENTRIES: usize = 500;
+const MAX_PERMISSION_AUDIT_ENTRIES: usize = 200;
+const PENDING_TRADE_MARKER: &str = "pending-trade.json";
+const CRASH_REPORT_FILE: &str = "crash-report.json";
+const BRIDGE_TOKEN_FILE: &str = "bridge-token";
+const BRIDGE_FALLBACK_PORTS: u16 = 10;
+// Requests handled at once; each /execute-trade holds a worker until the trade resolves
+const MAX_BRIDGE_WORKERS: usize = 32;
+const BRIDGE_PORT_ENV: &str = "HL_TRADER_BRIDGE_PORT";
+const BRIDGE_BIND_ENV: &str = "HL_TRADER_BRIDGE_BIND";
+const BRIDGE_RISK_ACK_ENV: &str = "HL_TRADER_BRIDGE_I_UNDERSTAND_THE_RISK";
 
 // ============ Biometric Authentication Result ============
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,10 +41,236 @@ pub struct BiometricResult {
     error: Option<String>,
 }
 
+// ============ Platform Capabilities ============
+// Detected once at startup so backends are picked up front instead of failing at call time
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformCapabilities {
+    os: String,
+    /// Architecture the app was built for
+    arch: String,
+    /// Architecture of the machine (differs when running under Rosetta or x64 emulation on ARM)
+    native_arch: String,
+    emulated: bool,
+    under_wine: bool,
+    desktop_environment: Option<String>,
+    /// External tools the platform backends rely on, and whether they were found
+    tools: std::collections::BTreeMap<String, bool>,
+    biometric_backend: Option<String>,
+    secure_storage_backend: String,
+}
+
+static PLATFORM_CAPABILITIES: std::sync::OnceLock<PlatformCapabilities> = std::sync::OnceLock::new();
+
+fn platform_capabilities() -> &'static PlatformCapabilities {
+    PLATFORM_CAPABILITIES.get_or_init(detect_platform_capabilities)
+}
+
+fn command_exists(name: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths).any(|dir| {
+                let candidate = dir.join(name);
+                candidate.is_file() || (cfg!(windows) && candidate.with_extension("exe").is_file())
+            })
+        })
+        .unwrap_or(false)
+}
+
+fn detect_platform_capabilities() -> PlatformCapabilities {
+    #[allow(unused_mut)]
+    let mut caps = PlatformCapabilities {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        native_arch: std::env::consts::ARCH.to_string(),
+        emulated: false,
+        under_wine: false,
+        desktop_environment: None,
+        tools: std::collections::BTreeMap::new(),
+        biometric_backend: None,
+        secure_storage_backend: "file".to_string(),
+    };
+
+    #[cfg(target_os = "linux")]
+    {
+        caps.desktop_environment = std::env::var("XDG_CURRENT_DESKTOP")
+            .or_else(|_| std::env::var("DESKTOP_SESSION"))
+            .ok();
+        for tool in ["zenity", "kdialog", "pkexec", "sudo"] {
+            caps.tools.insert(tool.to_string(), command_exists(tool));
+        }
+        // Password prompts are verified with sudo, so a dialog tool alone isn't enough
+        let has = |tool: &str| caps.tools.get(tool).copied().unwrap_or(false);
+        caps.biometric_backend = if !has("sudo") {
+            None
+        } else if has("zenity") {
+            Some("zenity".to_string())
+        } else if has("kdialog") {
+            Some("kdialog".to_string())
+        } else {
+            None
+        };
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+
+        let translated = Command::new("sysctl")
+            .args(["-n", "sysctl.proc_translated"])
+            .output()
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim() == "1")
+            .unwrap_or(false);
+        if translated {
+            caps.emulated = true;
+            caps.native_arch = "aarch64".to_string();
+        }
+        caps.tools.insert("bioutil".to_string(), command_exists("bioutil"));
+        caps.tools.insert("osascript".to_string(), command_exists("osascript"));
+        if touch_id_available() {
+            caps.biometric_backend = Some("touch_id".to_string());
+        }
+        caps.secure_storage_backend = "keychain".to_string();
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::process::Command;
+
+        // PROCESSOR_ARCHITEW6432 is only set for emulated processes and holds the real arch
+        let native = std::env::var("PROCESSOR_ARCHITEW6432")
+            .or_else(|_| std::env::var("PROCESSOR_ARCHITECTURE"))
+            .unwrap_or_default();
+        caps.native_arch = match native.to_uppercase().as_str() {
+            "ARM64" => "aarch64".to_string(),
+            "AMD64" => "x86_64".to_string(),
+            "X86" => "x86".to_string(),
+            _ => caps.arch.clone(),
+        };
+        caps.emulated = caps.native_arch != caps.arch;
+        caps.under_wine = Command::new("reg")
+            .args(["query", r"HKLM\Software\Wine"])
+            .output()
+            .map(|out| out.status.success())
+            .unwrap_or(false);
+        caps.tools.insert("powershell".to_string(), command_exists("powershell"));
+        // Windows Hello (and Credential Manager) aren't implemented by Wine/Proton
+        if !caps.under_wine && caps.tools.get("powershell").copied().unwrap_or(false) {
+            caps.biometric_backend = Some("windows_hello".to_string());
+        }
+    }
+
+    caps
+}
+
+#[cfg(not(target_os = "macos"))]
+fn capability_unavailable(what: &str) -> BiometricResult {
+    let caps = platform_capabilities();
+    BiometricResult {
+        success: false,
+        available: false,
+        error: Some(format!(
+            "capability_unavailable: {} (os={}, arch={}, native_arch={}, wine={}, desktop={}, tools={:?})",
+            what,
+            caps.os,
+            caps.arch,
+            caps.native_arch,
+            caps.under_wine,
+            caps.desktop_environment.as_deref().unwrap_or("unknown"),
+            caps.tools
+        )),
+    }
+}
+
+#[tauri::command]
+fn get_platform_capabilities() -> PlatformCapabilities {
+    platform_capabilities().clone()
+}
+
+// ============ Command Permissions ============
+// Commands are grouped into tiers and each window label may only invoke the tiers listed
+// for it. Read-only commands are open to every window and are not checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CommandTier {
+    ReadOnly,
+    Trading,
+    Secrets,
+    Admin,
+}
+
+fn window_tiers(label: &str) -> &'static [CommandTier] {
+    match label {
+        "main" => &[CommandTier::ReadOnly, CommandTier::Trading, CommandTier::Secrets, CommandTier::Admin],
+        _ => &[CommandTier::ReadOnly],
+    }
+}
+
+/// Check the calling window may invoke `command`; errors start with "permission_denied"
+fn authorize<R: tauri::Runtime>(window: &tauri::Window<R>, command: &str, tier: CommandTier) -> Result<(), String> {
+    let label = window.label();
+    if window_tiers(label).contains(&tier) {
+        return Ok(());
+    }
+    eprintln!("Permission denied: window '{}' invoked {} ({:?} tier)", label, command, tier);
+    record_permission_denial(label, command, tier);
+    Err(format!("permission_denied: {} is not allowed from window '{}'", command, label))
+}
+
+// Denied command calls, oldest first (get_permission_audit)
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionAuditEntry {
+    window: String,
+    command: String,
+    tier: CommandTier,
+    /// Unix time in milliseconds
+    timestamp: u64,
+}
+
+static PERMISSION_AUDIT: std::sync::OnceLock<Mutex<std::collections::VecDeque<PermissionAuditEntry>>> = std::sync::OnceLock::new();
+
+fn permission_audit() -> &'static Mutex<std::collections::VecDeque<PermissionAuditEntry>> {
+    PERMISSION_AUDIT.get_or_init(|| Mutex::new(std::collections::VecDeque::with_capacity(MAX_PERMISSION_AUDIT_ENTRIES)))
+}
+
+fn record_permission_denial(window: &str, command: &str, tier: CommandTier) {
+    if let Ok(mut audit) = permission_audit().lock() {
+        if audit.len() == MAX_PERMISSION_AUDIT_ENTRIES {
+            audit.pop_front();
+        }
+        audit.push_back(PermissionAuditEntry {
+            window: window.to_string(),
+            command: command.to_string(),
+            tier,
+            timestamp: unix_millis(),
+        });
+    }
+}
+
+#[tauri::command]
+fn get_permission_audit<R: tauri::Runtime>(window: tauri::Window<R>) -> Result<Vec<PermissionAuditEntry>, String> {
+    authorize(&window, "get_permission_audit", CommandTier::Admin)?;
+    Ok(permission_audit().lock().map(|audit| audit.iter().cloned().collect()).unwrap_or_default())
+}
+
+// ============ Biometric Prompt Reason ============
+// The reason ends up in a JXA script (macOS), a PowerShell environment variable (Windows)
+// or a dialog argument (Linux); it is always passed as data, never spliced into code.
+const MAX_BIOMETRIC_REASON_CHARS: usize = 120;
+
+/// Strip control characters, collapse whitespace and cap the length of a prompt reason.
+/// Empty reasons are rejected with an "invalid_reason" error.
+fn sanitize_biometric_reason(reason: &str) -> Result<String, String> {
+    let cleaned: String = reason.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    let collapsed = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.is_empty() {
+        return Err("invalid_reason: biometric prompt reason must not be empty".to_string());
+    }
+    Ok(collapsed.chars().take(MAX_BIOMETRIC_REASON_CHARS).collect())
+}
+
 // ============ macOS Touch ID Implementation ============
 #[cfg(target_os = "macos")]
-#[tauri::command]
-fn check_biometric_available() -> BiometricResult {
+fn touch_id_available() -> bool {
     use std::process::Command;
 
     // Check if Touch ID is available by querying system_profiler
@@ -31,14 +278,20 @@ fn check_biometric_available() -> BiometricResult {
         .args(["-r"])
         .output();
 
-    let available = match output {
+    match output {
         Ok(out) => out.status.success(),
         Err(_) => {
             // bioutil not available, try alternative check
             // On Macs with Touch ID, this file exists
             std::path::Path::new("/usr/lib/pam/pam_tid.so.2").exists()
         }
-    };
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[tauri::command]
+fn check_biometric_available() -> BiometricResult {
+    let available = touch_id_available();
 
     BiometricResult {
         success: true,
@@ -49,9 +302,17 @@ fn check_biometric_available() -> BiometricResult {
 
 #[cfg(target_os = "macos")]
 #[tauri::command]
-fn authenticate_biometric(reason: String) -> BiometricResult {
+fn authenticate_biometric(window: tauri::Window, reason: String) -> BiometricResult {
     use std::process::Command;
 
+    if let Err(e) = authorize(&window, "authenticate_biometric", CommandTier::Secrets) {
+        return BiometricResult { success: false, available: false, error: Some(e) };
+    }
+    let reason = match sanitize_biometric_reason(&reason) {
+        Ok(reason) => reason,
+        Err(e) => return BiometricResult { success: false, available: false, error: Some(e) },
+    };
+
     // First check if Touch ID is available
     let check = check_biometric_available();
     if !check.available {
@@ -77,7 +338,7 @@ if (!context.canEvaluatePolicyError($.LAPolicyDeviceOwnerAuthenticationWithBiome
     var result = 'pending';
     context.evaluatePolicyLocalizedReasonReply(
         $.LAPolicyDeviceOwnerAuthenticationWithBiometrics,
-        "{}",
+        {},
         function(success, authError) {{
             result = success ? 'success' : 'failed';
         }}
@@ -92,7 +353,11 @@ if (!context.canEvaluatePolicyError($.LAPolicyDeviceOwnerAuthenticationWithBiome
     result;
 }}
 "#,
-        reason.replace("\"", "\\\"").replace("'", "\\'")
+        // A JSON string is a valid JS string literal; escape the two separators JSON allows raw
+        serde_json::to_string(&reason)
+            .unwrap_or_else(|_| "\"\"".to_string())
+            .replace('\u{2028}', "\\u2028")
+            .replace('\u{2029}', "\\u2029")
     );
 
     let output = Command::new("osascript")
@@ -145,6 +410,10 @@ if (!context.canEvaluatePolicyError($.LAPolicyDeviceOwnerAuthenticationWithBiome
 fn check_biometric_available() -> BiometricResult {
     use std::process::Command;
 
+    if platform_capabilities().biometric_backend.is_none() {
+        return capability_unavailable("Windows Hello requires PowerShell and is unavailable under Wine/Proton");
+    }
+
     // Check if Windows Hello is available using PowerShell
     let output = Command::new("powershell")
         .args(["-Command", r#"
@@ -174,19 +443,33 @@ fn check_biometric_available() -> BiometricResult {
 
 #[cfg(target_os = "windows")]
 #[tauri::command]
-fn authenticate_biometric(reason: String) -> BiometricResult {
+fn authenticate_biometric(window: tauri::Window, reason: String) -> BiometricResult {
     use std::process::Command;
 
+    if let Err(e) = authorize(&window, "authenticate_biometric", CommandTier::Secrets) {
+        return BiometricResult { success: false, available: false, error: Some(e) };
+    }
+    let reason = match sanitize_biometric_reason(&reason) {
+        Ok(reason) => reason,
+        Err(e) => return BiometricResult { success: false, available: false, error: Some(e) },
+    };
+
+    if platform_capabilities().biometric_backend.is_none() {
+        return capability_unavailable("Windows Hello requires PowerShell and is unavailable under Wine/Proton");
+    }
+
     // Use Windows Hello for authentication
-    let script = format!(r#"
+    // The reason is read from the environment so PowerShell never parses it
+    let script = r#"
         Add-Type -AssemblyName System.Runtime.WindowsRuntime
         $null = [Windows.Security.Credentials.UI.UserConsentVerifier,Windows.Security.Credentials.UI,ContentType=WindowsRuntime]
-        $result = [Windows.Security.Credentials.UI.UserConsentVerifier]::RequestVerificationAsync("{}").GetAwaiter().GetResult()
-        if ($result -eq 'Verified') {{ 'success' }} else {{ 'failed' }}
-    "#, reason.replace("\"", "`\""));
+        $result = [Windows.Security.Credentials.UI.UserConsentVerifier]::RequestVerificationAsync($env:HL_TRADER_BIOMETRIC_REASON).GetAwaiter().GetResult()
+        if ($result -eq 'Verified') { 'success' } else { 'failed' }
+    "#;
 
     let output = Command::new("powershell")
-        .args(["-Command", &script])
+        .args(["-Command", script])
+        .env("HL_TRADER_BIOMETRIC_REASON", &reason)
         .output();
 
     match output {
@@ -220,64 +503,50 @@ fn authenticate_biometric(reason: String) -> BiometricResult {
 #[cfg(target_os = "linux")]
 #[tauri::command]
 fn check_biometric_available() -> BiometricResult {
-    use std::process::Command;
-
-    // Check if pkexec (polkit) is available - standard on most Linux distros
-    let output = Command::new("which")
-        .arg("pkexec")
-        .output();
-
-    let available = output.map(|o| o.status.success()).unwrap_or(false);
+    // Authentication prompts with zenity/kdialog and verifies with sudo
+    if platform_capabilities().biometric_backend.is_none() {
+        let mut result = capability_unavailable("System authentication needs zenity or kdialog, and sudo");
+        result.success = true;
+        return result;
+    }
 
     BiometricResult {
         success: true,
-        available,
-        error: if available { None } else { Some("System authentication not available".to_string()) },
+        available: true,
+        error: None,
     }
 }
 
 #[cfg(target_os = "linux")]
 #[tauri::command]
-fn authenticate_biometric(reason: String) -> BiometricResult {
+fn authenticate_biometric(window: tauri::Window, reason: String) -> BiometricResult {
     use std::process::Command;
 
-    // Use zenity or kdialog for password prompt with system auth
-    // Try zenity first (GTK), then kdialog (KDE)
-    let zenity_result = Command::new("zenity")
-        .args(["--password", "--title", &reason])
-        .output();
-
-    if let Ok(output) = zenity_result {
-        if output.status.success() {
-            // User entered password - verify with sudo -v
-            let password = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            let verify = Command::new("sh")
-                .args(["-c", &format!("echo '{}' | sudo -S -v 2>/dev/null", password)])
-                .output();
-
-            if verify.map(|v| v.status.success()).unwrap_or(false) {
-                return BiometricResult {
-                    success: true,
-                    available: true,
-                    error: None,
-                };
-            }
-        }
+    if let Err(e) = authorize(&window, "authenticate_biometric", CommandTier::Secrets) {
+        return BiometricResult { success: false, available: false, error: Some(e) };
     }
+    let reason = match sanitize_biometric_reason(&reason) {
+        Ok(reason) => reason,
+        Err(e) => return BiometricResult { success: false, available: false, error: Some(e) },
+    };
 
-    // Try kdialog as fallback
-    let kdialog_result = Command::new("kdialog")
-        .args(["--password", &reason])
-        .output();
+    // Use zenity (GTK) or kdialog (KDE) for password prompt with system auth,
+    // whichever was detected at startup
+    let prompt_result = match platform_capabilities().biometric_backend.as_deref() {
+        Some("zenity") => Command::new("zenity")
+            .args(["--password", "--title", &reason])
+            .output(),
+        Some("kdialog") => Command::new("kdialog")
+            .args(["--password", &reason])
+            .output(),
+        _ => return capability_unavailable("System authentication needs zenity or kdialog, and sudo"),
+    };
 
-    if let Ok(output) = kdialog_result {
+    if let Ok(output) = prompt_result {
         if output.status.success() {
-            let password = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            let verify = Command::new("sh")
-                .args(["-c", &format!("echo '{}' | sudo -S -v 2>/dev/null", password)])
-                .output();
-
-            if verify.map(|v| v.status.success()).unwrap_or(false) {
+            // User entered password - verify with sudo -v
+            let password = String::from_utf8_lossy(&output.stdout).trim_end_matches(['\r', '\n']).to_string();
+            if verify_sudo_password(&password) {
                 return BiometricResult {
                     success: true,
                     available: true,
@@ -294,63 +563,363 @@ fn authenticate_biometric(reason: String) -> BiometricResult {
     }
 }
 
-// Cross-platform secure storage path for Windows/Linux
-#[cfg(not(target_os = "macos"))]
-fn get_secure_storage_path() -> std::path::PathBuf {
+/// Check a password with `sudo -S -v`. The password goes to sudo's stdin, never through a
+/// shell or the argument list.
+#[cfg(target_os = "linux")]
+fn verify_sudo_password(password: &str) -> bool {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = match Command::new("sudo")
+        .args(["-S", "-v", "-p", ""])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Dropping stdin closes it, so sudo can't block waiting for a retry
+        let _ = stdin.write_all(format!("{}\n", password).as_bytes());
+    }
+    child.wait().map(|status| status.success()).unwrap_or(false)
+}
+
+// App data directory (config dir on every platform)
+fn get_app_data_dir() -> std::path::PathBuf {
     let mut path = dirs::config_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
     path.push("hyperliquid-trader");
     std::fs::create_dir_all(&path).ok();
+    path
+}
+
+// Cross-platform secure storage path for Windows/Linux
+#[cfg(not(target_os = "macos"))]
+fn get_secure_storage_path() -> std::path::PathBuf {
+    let mut path = get_app_data_dir();
     path.push(".vault");
     path
 }
 
 // Shared settings state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct BridgeSettings {
     pub risk: f64,
     pub leverage: u32,
     pub asset: String,
     pub price: f64,
+    /// How long quitting waits for a pending trade before giving up
+    pub shutdown_max_wait_secs: u64,
+    /// Grace countdown before a bridge trade is sent for execution (0 = off).
+    /// The countdown is part of the /execute-trade timeout, not added to it, and is capped so
+    /// at least MIN_TRADE_RESULT_WAIT_SECS of the timeout are left for the result.
+    pub countdown_secs: u64,
+    /// How long /execute-trade waits in total, countdown included, before answering 408 (5-300)
+    pub trade_timeout_secs: u64,
+    /// Identical trades (same direction, entry, stop, risk, leverage) this close together are
+    /// treated as one; 0 = off. Requests with an Idempotency-Key header are matched on that instead.
+    pub duplicate_trade_window_secs: u64,
+    /// Highest leverage a bridge trade may ask for
+    pub max_leverage: u32,
+    /// Stop distance (% of last price) quick_trade uses when no chart position is tracked
+    pub quick_trade_stop_pct: f64,
+    /// Price moves below this (% of the level) count as unchanged when the extension resends a position
+    pub position_change_tolerance_pct: f64,
+    /// Positions whose timestamp is older than this are rejected as stale replays
+    pub position_max_age_secs: u64,
+    /// Extension counts as disconnected after this long without a heartbeat
+    pub extension_heartbeat_timeout_secs: u64,
+    /// What to do with payloads mixing camelCase and snake_case fields: allow, warn or reject
+    pub mixed_naming_policy: String,
+    /// Strategy tags the extension may put on trades, besides "default"
+    pub strategies: Vec<String>,
+    /// Bridge listener port; HL_TRADER_BRIDGE_PORT takes precedence when set
+    pub bridge_port: u16,
+    /// Origins allowed to call the bridge from a browser (CORS); requests without an Origin
+    /// header still need the bridge token
+    pub allowed_origins: Vec<String>,
+    /// Unix seconds of the last change, for Last-Modified on GET /settings (whole seconds, so
+    /// conditional requests should prefer the ETag)
+    #[serde(skip)]
+    pub modified_at: u64,
 }
 
 impl Default for BridgeSettings {
     fn default() -> Self {
-        BridgeSettings { risk: 1.0, leverage: 25, asset: "BTC".to_string(), price: 0.0 }
+        BridgeSettings {
+            risk: 1.0,
+            leverage: 25,
+            asset: "BTC".to_string(),
+            price: 0.0,
+            shutdown_max_wait_secs: 30,
+            countdown_secs: 0,
+            trade_timeout_secs: DEFAULT_TRADE_TIMEOUT_SECS,
+            duplicate_trade_window_secs: 10,
+            max_leverage: 50,
+            quick_trade_stop_pct: 1.0,
+            position_change_tolerance_pct: 0.01,
+            position_max_age_secs: 30,
+            extension_heartbeat_timeout_secs: 15,
+            mixed_naming_policy: "warn".to_string(),
+            strategies: Vec::new(),
+            bridge_port: BRIDGE_PORT,
+            allowed_origins: DEFAULT_ALLOWED_ORIGINS.iter().map(|o| o.to_string()).collect(),
+            modified_at: unix_millis() / 1000,
+        }
     }
 }
 
 // Trade result from frontend
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TradeResult {
     pub success: bool,
     pub error: Option<String>,
+    #[serde(skip)]
+    pub resolution: TradeResolution,
 }
 
-// Pending trade result channel
+/// What ended a pending trade
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TradeResolution {
+    /// The frontend reported the outcome
+    #[default]
+    Reported,
+    /// Cancelled through POST /cancel-trade
+    Cancelled,
+    /// Nothing arrived within trade_timeout_secs
+    TimedOut,
+    /// The app shut down before the trade resolved
+    Interrupted,
+}
+
+// Pending trade result channels, keyed by trade id so overlapping trades get their own result
 use std::sync::mpsc::{channel, Sender};
-static TRADE_RESULT_SENDER: std::sync::OnceLock<Mutex<Option<Sender<TradeResult>>>> = std::sync::OnceLock::new();
+static PENDING_TRADES: std::sync::OnceLock<Mutex<std::collections::HashMap<String, PendingTrade>>> = std::sync::OnceLock::new();
+
+// Trade waiting on the frontend, with the channel its HTTP request listens on
+struct PendingTrade {
+    request: TradeRequest,
+    sender: Sender<TradeResult>,
+    started_at: Instant,
+    /// X-Request-Id of the /execute-trade request that registered it
+    request_id: String,
+    /// Set (under the pending lock) when the trade is emitted to the frontend; from then on
+    /// only its result or the timeout can end it, not a cancel
+    executing: bool,
+}
+
+fn pending_trades() -> &'static Mutex<std::collections::HashMap<String, PendingTrade>> {
+    PENDING_TRADES.get_or_init(|| Mutex::new(std::collections::HashMap::new()))
+}
+
+// Recently resolved trades, so a late cancel or a /trade-status poll can be told how the trade
+// ended. Kept for COMPLETED_TRADE_TTL_SECS, and never more than MAX_COMPLETED_TRADES.
+const MAX_COMPLETED_TRADES: usize = 200;
+const COMPLETED_TRADE_TTL_SECS: u64 = 300;
+static COMPLETED_TRADES: std::sync::OnceLock<Mutex<std::collections::VecDeque<CompletedTrade>>> = std::sync::OnceLock::new();
+
+struct CompletedTrade {
+    trade_id: String,
+    request_id: String,
+    result: TradeResult,
+    completed_at: Instant,
+}
+
+fn completed_trades() -> &'static Mutex<std::collections::VecDeque<CompletedTrade>> {
+    COMPLETED_TRADES.get_or_init(|| Mutex::new(std::collections::VecDeque::new()))
+}
+
+/// Result a recently resolved trade ended with, evicting expired entries on the way
+fn completed_trade(trade_id: &str) -> Option<TradeResult> {
+    completed_trade_record(trade_id).map(|(result, _)| result)
+}
+
+/// Result and request id of a recently resolved trade, evicting expired entries on the way
+fn completed_trade_record(trade_id: &str) -> Option<(TradeResult, String)> {
+    let mut completed = completed_trades().lock().ok()?;
+    let ttl = Duration::from_secs(COMPLETED_TRADE_TTL_SECS);
+    while completed.front().is_some_and(|trade| trade.completed_at.elapsed() > ttl) {
+        completed.pop_front();
+    }
+    completed
+        .iter()
+        .rev()
+        .find(|trade| trade.trade_id == trade_id)
+        .map(|trade| (trade.result.clone(), trade.request_id.clone()))
+}
+
+/// Resolve a pending trade exactly once: the first caller (result, cancel or timeout) removes it,
+/// records the result and wakes its HTTP request. Later callers get the result it ended with,
+/// or None for an id that was never pending.
+fn resolve_pending_trade(trade_id: &str, result: TradeResult) -> Result<(), Option<TradeResult>> {
+    // Lock order: pending trades, then completed trades
+    let mut pending = pending_trades().lock().map_err(|_| None)?;
+    resolve_locked(&mut pending, trade_id, result)
+}
+
+// resolve_pending_trade with the pending lock already held
+fn resolve_locked(
+    pending: &mut std::collections::HashMap<String, PendingTrade>,
+    trade_id: &str,
+    result: TradeResult,
+) -> Result<(), Option<TradeResult>> {
+    match pending.remove(trade_id) {
+        Some(trade) => {
+            if let Ok(mut completed) = completed_trades().lock() {
+                if completed.len() >= MAX_COMPLETED_TRADES {
+                    completed.pop_front();
+                }
+                completed.push_back(CompletedTrade {
+                    trade_id: trade_id.to_string(),
+                    request_id: trade.request_id.clone(),
+                    result: result.clone(),
+                    completed_at: Instant::now(),
+                });
+            }
+            let _ = trade.sender.send(result);
+            Ok(())
+        }
+        None => Err(completed_trade(trade_id)),
+    }
+}
+
+// Why /cancel-trade could not cancel a trade
+enum CancelRefused {
+    /// Already emitted to the frontend, which may be placing orders
+    Executing,
+    /// Resolved earlier, with this result
+    Completed(TradeResult),
+    Unknown,
+}
+
+/// Cancel a trade that hasn't reached the frontend yet. Checked and resolved under one lock, so
+/// it can't slip in between run_pending_trade emitting the trade and the result coming back.
+fn cancel_pending_trade(trade_id: &str) -> Result<(), CancelRefused> {
+    let mut pending = pending_trades().lock().map_err(|_| CancelRefused::Unknown)?;
+    if pending.get(trade_id).is_some_and(|trade| trade.executing) {
+        return Err(CancelRefused::Executing);
+    }
+    let cancelled = TradeResult {
+        success: false,
+        error: Some("cancelled".to_string()),
+        resolution: TradeResolution::Cancelled,
+    };
+    resolve_locked(&mut pending, trade_id, cancelled).map_err(|completed| match completed {
+        Some(result) => CancelRefused::Completed(result),
+        None => CancelRefused::Unknown,
+    })
+}
+
+/// `{tradeId, status, error, requestId}` for a trade: pending, success, failed, interrupted (the
+/// app quit while it was pending), or unknown once evicted. requestId is the X-Request-Id of the
+/// /execute-trade request that placed it.
+fn trade_status(trade_id: &str) -> serde_json::Value {
+    let pending = pending_trades().lock().ok().and_then(|guard| guard.get(trade_id).map(|trade| trade.request_id.clone()));
+    if let Some(request_id) = pending {
+        return serde_json::json!({ "tradeId": trade_id, "status": "pending", "requestId": request_id });
+    }
+    match completed_trade_record(trade_id) {
+        Some((result, request_id)) => serde_json::json!({
+            "tradeId": trade_id,
+            "status": match result.resolution {
+                TradeResolution::Interrupted => "interrupted",
+                _ if result.success => "success",
+                _ => "failed",
+            },
+            "error": result.error,
+            "requestId": request_id,
+        }),
+        None => serde_json::json!({ "tradeId": trade_id, "status": "unknown" }),
+    }
+}
+
+fn is_valid_trade_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[derive(Debug, Deserialize)]
+struct CancelTradeRequest {
+    #[serde(rename = "tradeId", alias = "trade_id")]
+    trade_id: String,
+}
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct PositionData {
     direction: String,
     entry: f64,
-    #[serde(rename = "stopLoss")]
+    #[serde(rename = "stopLoss", alias = "stop_loss")]
     stop_loss: f64,
-    #[serde(rename = "takeProfit")]
+    #[serde(rename = "takeProfit", alias = "take_profit")]
     take_profit: Option<f64>,
     timestamp: u64,
+    /// Asset or TradingView symbol; legacy payloads omit it and get the active settings asset
+    #[serde(default)]
+    asset: Option<String>,
+    #[serde(default, rename = "chartContext", alias = "chart_context", skip_serializing_if = "Option::is_none")]
+    chart_context: Option<ChartContext>,
+}
+
+/// Chart the extension was looking at, passed through untouched for later analysis
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct ChartContext {
+    /// TradingView interval, e.g. "15" or "1D"
+    #[serde(default)]
+    interval: Option<String>,
+    /// Full TradingView symbol, e.g. "BYBIT:BTCUSDT.P"
+    #[serde(default)]
+    symbol: Option<String>,
+    #[serde(default, rename = "chartId", alias = "chart_id")]
+    chart_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Optional body for /position-closed; no asset means all tracked positions
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct PositionClosedRequest {
+    asset: Option<String>,
+    #[serde(rename = "requestId", skip_deserializing)]
+    request_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct TradeRequest {
     direction: String,
     entry: f64,
-    #[serde(rename = "stopLoss")]
+    #[serde(rename = "stopLoss", alias = "stop_loss")]
     stop_loss: f64,
-    #[serde(rename = "takeProfit")]
+    #[serde(rename = "takeProfit", alias = "take_profit")]
     take_profit: Option<f64>,
     risk: f64,
     leverage: u32,
+    #[serde(default, rename = "chartContext", alias = "chart_context", skip_serializing_if = "Option::is_none")]
+    chart_context: Option<ChartContext>,
+    /// One of the configured strategies; untagged trades go to the "default" bucket
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    strategy: Option<String>,
+    /// Optional client-chosen id, so the trade can be cancelled (POST /cancel-trade) while pending.
+    /// Generated when absent. The handler takes it out of the trade, so the execute event carries
+    /// it once, next to the trade; the pending-trade marker puts it back.
+    #[serde(default, rename = "tradeId", alias = "trade_id", skip_serializing_if = "Option::is_none")]
+    trade_id: Option<String>,
+}
+
+/// Secret string whose Debug output is redacted, so it can't end up in logs or crash reports
+#[derive(Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -369,10 +938,13 @@ pub struct KeychainGetResult {
 // ============ macOS Keychain Implementation ============
 #[cfg(target_os = "macos")]
 #[tauri::command]
-fn keychain_save(password: String) -> KeychainResult {
+fn keychain_save(window: tauri::Window, password: Secret) -> KeychainResult {
+    if let Err(e) = authorize(&window, "keychain_save", CommandTier::Secrets) {
+        return KeychainResult { success: false, error: Some(e) };
+    }
     let _ = delete_generic_password(SERVICE_NAME, ACCOUNT_NAME);
 
-    match set_generic_password(SERVICE_NAME, ACCOUNT_NAME, password.as_bytes()) {
+    match set_generic_password(SERVICE_NAME, ACCOUNT_NAME, password.expose().as_bytes()) {
         Ok(()) => KeychainResult {
             success: true,
             error: None,
@@ -386,7 +958,10 @@ fn keychain_save(password: String) -> KeychainResult {
 
 #[cfg(target_os = "macos")]
 #[tauri::command]
-fn keychain_load() -> KeychainGetResult {
+fn keychain_load(window: tauri::Window) -> KeychainGetResult {
+    if let Err(e) = authorize(&window, "keychain_load", CommandTier::Secrets) {
+        return KeychainGetResult { success: false, password: None, error: Some(e) };
+    }
     match get_generic_password(SERVICE_NAME, ACCOUNT_NAME) {
         Ok(password_bytes) => {
             match String::from_utf8(password_bytes.to_vec()) {
@@ -423,7 +998,10 @@ fn keychain_load() -> KeychainGetResult {
 
 #[cfg(target_os = "macos")]
 #[tauri::command]
-fn keychain_delete() -> KeychainResult {
+fn keychain_delete(window: tauri::Window) -> KeychainResult {
+    if let Err(e) = authorize(&window, "keychain_delete", CommandTier::Secrets) {
+        return KeychainResult { success: false, error: Some(e) };
+    }
     match delete_generic_password(SERVICE_NAME, ACCOUNT_NAME) {
         Ok(()) => KeychainResult {
             success: true,
@@ -448,16 +1026,20 @@ fn keychain_delete() -> KeychainResult {
 
 #[cfg(target_os = "macos")]
 #[tauri::command]
-fn keychain_has_password() -> bool {
-    get_generic_password(SERVICE_NAME, ACCOUNT_NAME).is_ok()
+fn keychain_has_password<R: tauri::Runtime>(window: tauri::Window<R>) -> Result<bool, String> {
+    authorize(&window, "keychain_has_password", CommandTier::Secrets)?;
+    Ok(get_generic_password(SERVICE_NAME, ACCOUNT_NAME).is_ok())
 }
 
 // ============ Windows/Linux File-based Implementation ============
 #[cfg(not(target_os = "macos"))]
 #[tauri::command]
-fn keychain_save(password: String) -> KeychainResult {
+fn keychain_save(window: tauri::Window, password: Secret) -> KeychainResult {
+    if let Err(e) = authorize(&window, "keychain_save", CommandTier::Secrets) {
+        return KeychainResult { success: false, error: Some(e) };
+    }
     let path = get_secure_storage_path();
-    match std::fs::write(&path, password.as_bytes()) {
+    match std::fs::write(&path, password.expose().as_bytes()) {
         Ok(()) => {
             // Try to set restrictive permissions on Unix-like systems
             #[cfg(unix)]
@@ -479,7 +1061,10 @@ fn keychain_save(password: String) -> KeychainResult {
 
 #[cfg(not(target_os = "macos"))]
 #[tauri::command]
-fn keychain_load() -> KeychainGetResult {
+fn keychain_load(window: tauri::Window) -> KeychainGetResult {
+    if let Err(e) = authorize(&window, "keychain_load", CommandTier::Secrets) {
+        return KeychainGetResult { success: false, password: None, error: Some(e) };
+    }
     let path = get_secure_storage_path();
     match std::fs::read_to_string(&path) {
         Ok(password) => KeychainGetResult {
@@ -507,7 +1092,10 @@ fn keychain_load() -> KeychainGetResult {
 
 #[cfg(not(target_os = "macos"))]
 #[tauri::command]
-fn keychain_delete() -> KeychainResult {
+fn keychain_delete(window: tauri::Window) -> KeychainResult {
+    if let Err(e) = authorize(&window, "keychain_delete", CommandTier::Secrets) {
+        return KeychainResult { success: false, error: Some(e) };
+    }
     let path = get_secure_storage_path();
     match std::fs::remove_file(&path) {
         Ok(()) => KeychainResult {
@@ -532,292 +1120,4242 @@ fn keychain_delete() -> KeychainResult {
 
 #[cfg(not(target_os = "macos"))]
 #[tauri::command]
-fn keychain_has_password() -> bool {
-    get_secure_storage_path().exists()
+fn keychain_has_password<R: tauri::Runtime>(window: tauri::Window<R>) -> Result<bool, String> {
+    authorize(&window, "keychain_has_password", CommandTier::Secrets)?;
+    Ok(get_secure_storage_path().exists())
 }
 
 /// Update bridge settings from frontend
 #[tauri::command]
-fn update_bridge_settings(state: tauri::State<Arc<Mutex<BridgeSettings>>>, risk: f64, leverage: u32, asset: String, price: f64) {
+#[allow(clippy::too_many_arguments)]
+fn update_bridge_settings(app: tauri::AppHandle, window: tauri::Window, state: tauri::State<Arc<Mutex<BridgeSettings>>>, risk: f64, leverage: u32, asset: String, price: f64, shutdown_max_wait_secs: Option<u64>, countdown_secs: Option<u64>, trade_timeout_secs: Option<u64>, duplicate_trade_window_secs: Option<u64>, max_leverage: Option<u32>, quick_trade_stop_pct: Option<f64>, position_change_tolerance_pct: Option<f64>, position_max_age_secs: Option<u64>, extension_heartbeat_timeout_secs: Option<u64>, mixed_naming_policy: Option<String>, strategies: Option<Vec<String>>, bridge_port: Option<u16>) -> Result<(), String> {
+    authorize(&window, "update_bridge_settings", CommandTier::Trading)?;
     let mut settings = state.lock().unwrap();
+    let before = settings.clone();
     settings.risk = risk;
     settings.leverage = leverage;
     settings.asset = asset;
     settings.price = price;
+    if let Some(secs) = shutdown_max_wait_secs {
+        settings.shutdown_max_wait_secs = secs;
+    }
+    if let Some(secs) = countdown_secs {
+        settings.countdown_secs = secs.min(MAX_COUNTDOWN_SECS);
+    }
+    if let Some(secs) = trade_timeout_secs {
+        settings.trade_timeout_secs = secs.clamp(5, 300);
+    }
+    settings.countdown_secs = countdown_within_budget(settings.countdown_secs, settings.trade_timeout_secs);
+    if let Some(secs) = duplicate_trade_window_secs {
+        settings.duplicate_trade_window_secs = secs.min(300);
+    }
+    if let Some(max) = max_leverage.filter(|max| (1..=200).contains(max)) {
+        settings.max_leverage = max;
+    }
+    if let Some(pct) = quick_trade_stop_pct.filter(|pct| *pct > 0.0 && *pct < 50.0) {
+        settings.quick_trade_stop_pct = pct;
+    }
+    if let Some(pct) = position_change_tolerance_pct.filter(|pct| *pct >= 0.0 && *pct < 5.0) {
+        settings.position_change_tolerance_pct = pct;
+    }
+    if let Some(secs) = position_max_age_secs {
+        settings.position_max_age_secs = secs.clamp(1, 300);
+    }
+    if let Some(secs) = extension_heartbeat_timeout_secs {
+        settings.extension_heartbeat_timeout_secs = secs.clamp(5, 300);
+    }
+    if let Some(policy) = mixed_naming_policy.filter(|p| MIXED_NAMING_POLICIES.contains(&p.as_str())) {
+        settings.mixed_naming_policy = policy;
+    }
+    if let Some(strategies) = strategies {
+        let mut cleaned: Vec<String> = strategies.iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        cleaned.sort();
+        cleaned.dedup();
+        settings.strategies = cleaned;
+    }
+    if let Some(port) = bridge_port.filter(|port| *port >= 1024) {
+        settings.bridge_port = port;
+    }
+    if *settings != before {
+        settings.modified_at = unix_millis() / 1000;
+        next_state_seq();
+    }
+    let restart = configured_bridge_port(&settings) != configured_bridge_port(&before);
+    drop(settings);
+
+    if restart {
+        restart_bridge_server(app, state.inner().clone());
+    }
+    Ok(())
 }
 
-/// Report trade result from frontend back to HTTP server
+/// Longest countdown that still leaves MIN_TRADE_RESULT_WAIT_SECS of the trade timeout
+fn countdown_within_budget(countdown_secs: u64, trade_timeout_secs: u64) -> u64 {
+    countdown_secs.min(trade_timeout_secs.saturating_sub(MIN_TRADE_RESULT_WAIT_SECS))
+}
+
+/// Report trade result from frontend back to the HTTP request waiting on `trade_id`
 #[tauri::command]
-fn report_trade_result(success: bool, error: Option<String>) {
-    let result = TradeResult { success, error };
-    if let Some(sender_lock) = TRADE_RESULT_SENDER.get() {
-        if let Ok(guard) = sender_lock.lock() {
-            if let Some(sender) = guard.as_ref() {
-                let _ = sender.send(result);
-            }
+fn report_trade_result(window: tauri::Window, trade_id: String, success: bool, error: Option<String>) -> Result<(), String> {
+    authorize(&window, "report_trade_result", CommandTier::Trading)?;
+    let result = TradeResult { success, error, resolution: TradeResolution::Reported };
+    match resolve_pending_trade(&trade_id, result) {
+        Ok(()) => Ok(()),
+        // Timed out, cancelled or already answered - nobody is waiting for this result any more
+        Err(Some(_)) => Err(format!("already_completed: {}", trade_id)),
+        Err(None) => Err(format!("unknown_trade: {}", trade_id)),
+    }
+}
+
+// ============ Recent Bridge Errors ============
+// User-facing errors kept for the extension to render as toasts (GET /last-error)
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeError {
+    code: String,
+    message: String,
+    #[serde(rename = "tradeId")]
+    trade_id: Option<String>,
+    #[serde(rename = "requestId")]
+    request_id: Option<String>,
+    /// Unix time in milliseconds
+    timestamp: u64,
+}
+
+static RECENT_ERRORS: std::sync::OnceLock<Mutex<std::collections::VecDeque<BridgeError>>> = std::sync::OnceLock::new();
+
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn record_bridge_error(code: &str, message: &str, trade_id: Option<&str>, request_id: Option<&str>) {
+    let lock = RECENT_ERRORS.get_or_init(|| Mutex::new(std::collections::VecDeque::with_capacity(MAX_RECENT_ERRORS)));
+    if let Ok(mut errors) = lock.lock() {
+        if errors.len() == MAX_RECENT_ERRORS {
+            errors.pop_front();
         }
+        errors.push_back(BridgeError {
+            code: code.to_string(),
+            message: message.to_string(),
+            trade_id: trade_id.map(|id| id.to_string()),
+            request_id: request_id.map(|id| id.to_string()),
+            timestamp: unix_millis(),
+        });
     }
 }
 
-// ============ HTTP Proxy for CORS bypass ============
-#[derive(Debug, Serialize, Deserialize)]
-pub struct HttpResponse {
-    success: bool,
-    data: Option<String>,
-    error: Option<String>,
-    status: u16,
+/// Errors recorded after `since` (unix millis), oldest first
+fn recent_bridge_errors(since: u64) -> Vec<BridgeError> {
+    RECENT_ERRORS
+        .get()
+        .and_then(|lock| lock.lock().ok())
+        .map(|errors| errors.iter().filter(|e| e.timestamp > since).cloned().collect())
+        .unwrap_or_default()
 }
 
-/// HTTP GET request - bypasses CORS by making request from Rust
-#[tauri::command]
-async fn http_get(url: String) -> HttpResponse {
-    match reqwest::get(&url).await {
-        Ok(response) => {
-            let status = response.status().as_u16();
-            match response.text().await {
-                Ok(text) => HttpResponse {
-                    success: status >= 200 && status < 300,
-                    data: Some(text),
-                    error: None,
-                    status,
-                },
-                Err(e) => HttpResponse {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Failed to read response: {}", e)),
-                    status,
-                },
+// ============ Position Freshness ============
+// Clock skew tolerated for position timestamps ahead of ours
+const POSITION_FUTURE_SKEW_MS: u64 = 5_000;
+
+/// Why a position with this timestamp (Unix ms) shouldn't be shown, if it shouldn't
+fn stale_position_reason(timestamp_ms: u64, now_ms: u64, max_age_secs: u64) -> Option<String> {
+    if timestamp_ms > now_ms.saturating_add(POSITION_FUTURE_SKEW_MS) {
+        return Some(format!("Position timestamp is {}ms in the future", timestamp_ms - now_ms));
+    }
+    let age_ms = now_ms.saturating_sub(timestamp_ms);
+    if age_ms > max_age_secs.saturating_mul(1000) {
+        return Some(format!("Position is {}s old (max {}s)", age_ms / 1000, max_age_secs));
+    }
+    None
+}
+
+// ============ Tracked Chart Positions ============
+// Latest position tool per asset, so drawings on several charts don't clobber each other
+static TRACKED_POSITIONS: std::sync::OnceLock<Mutex<std::collections::HashMap<String, PositionData>>> = std::sync::OnceLock::new();
+
+fn tracked_positions() -> &'static Mutex<std::collections::HashMap<String, PositionData>> {
+    TRACKED_POSITIONS.get_or_init(|| Mutex::new(std::collections::HashMap::new()))
+}
+
+/// Map a TradingView symbol ("BINANCE:BTCUSDT.P", "BTCUSD") to the app's asset name ("BTC")
+fn resolve_asset(symbol: &str) -> String {
+    let symbol = symbol.rsplit(':').next().unwrap_or(symbol).trim();
+    let symbol = symbol.trim_end_matches(".P").trim_end_matches("PERP");
+    for quote in ["USDT", "USDC", "USD"] {
+        if let Some(base) = symbol.strip_suffix(quote) {
+            if !base.is_empty() {
+                return base.to_string();
             }
         }
-        Err(e) => HttpResponse {
-            success: false,
-            data: None,
-            error: Some(format!("Request failed: {}", e)),
-            status: 0,
-        },
     }
+    symbol.to_string()
 }
 
-/// HTTP POST request - bypasses CORS
-#[tauri::command]
-async fn http_post(url: String, body: String) -> HttpResponse {
-    let client = reqwest::Client::new();
-    match client.post(&url)
-        .header("Content-Type", "application/json")
-        .body(body)
-        .send()
-        .await {
-        Ok(response) => {
-            let status = response.status().as_u16();
-            match response.text().await {
-                Ok(text) => HttpResponse {
-                    success: status >= 200 && status < 300,
-                    data: Some(text),
-                    error: None,
-                    status,
-                },
-                Err(e) => HttpResponse {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Failed to read response: {}", e)),
-                    status,
-                },
-            }
-        }
-        Err(e) => HttpResponse {
-            success: false,
-            data: None,
-            error: Some(format!("Request failed: {}", e)),
-            status: 0,
+// How a resent position tool differs from the one already tracked for its asset
+#[derive(Debug, Clone, Serialize)]
+struct PositionChange {
+    /// new, moved-entry, moved-stop, moved-tp or unchanged
+    kind: String,
+    deltas: PositionDeltas,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct PositionDeltas {
+    entry: f64,
+    #[serde(rename = "stopLoss")]
+    stop_loss: f64,
+    #[serde(rename = "takeProfit")]
+    take_profit: Option<f64>,
+}
+
+// tradingview-position event payload: the position plus what changed
+#[derive(Debug, Clone, Serialize)]
+struct PositionEvent {
+    #[serde(flatten)]
+    position: PositionData,
+    change: PositionChange,
+    #[serde(rename = "requestId")]
+    request_id: String,
+}
+
+fn classify_position_update(previous: Option<&PositionData>, next: &PositionData, tolerance_pct: f64) -> PositionChange {
+    let previous = match previous {
+        Some(previous) if previous.direction.eq_ignore_ascii_case(&next.direction) => previous,
+        _ => return PositionChange { kind: "new".to_string(), deltas: PositionDeltas::default() },
+    };
+
+    let moved = |from: f64, to: f64| (to - from).abs() > from.abs() * tolerance_pct / 100.0;
+    let deltas = PositionDeltas {
+        entry: next.entry - previous.entry,
+        stop_loss: next.stop_loss - previous.stop_loss,
+        take_profit: match (previous.take_profit, next.take_profit) {
+            (Some(from), Some(to)) => Some(to - from),
+            _ => None,
         },
-    }
+    };
+    let tp_moved = match (previous.take_profit, next.take_profit) {
+        (Some(from), Some(to)) => moved(from, to),
+        (None, None) => false,
+        _ => true,
+    };
+
+    // Dragging the whole tool moves entry along with the other levels, so entry wins
+    let kind = if moved(previous.entry, next.entry) {
+        "moved-entry"
+    } else if moved(previous.stop_loss, next.stop_loss) {
+        "moved-stop"
+    } else if tp_moved {
+        "moved-tp"
+    } else {
+        "unchanged"
+    };
+    PositionChange { kind: kind.to_string(), deltas }
 }
 
-/// Start the TradingView bridge HTTP server
-fn start_bridge_server(app_handle: tauri::AppHandle, settings: Arc<Mutex<BridgeSettings>>) {
-    thread::spawn(move || {
-        let server = match tiny_http::Server::http(format!("127.0.0.1:{}", BRIDGE_PORT)) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("Failed to start bridge server: {}", e);
-                return;
-            }
-        };
+// tradingview-execute-trade event payload
+#[derive(Debug, Clone, Serialize)]
+struct TradeExecuteEvent {
+    #[serde(flatten)]
+    trade: TradeRequest,
+    #[serde(rename = "tradeId")]
+    trade_id: String,
+    #[serde(rename = "requestId")]
+    request_id: String,
+}
+
+// ============ Trade Circuit Breaker ============
+// Stops forwarding trades after repeated exchange-side failures, so a maintenance window or a
+// bad agent key doesn't turn into a stream of rejected orders. Errors the user caused
+// (cancelled, insufficient margin) don't count towards opening the circuit.
+const CIRCUIT_FAILURE_THRESHOLD: usize = 5;
+const CIRCUIT_FAILURE_WINDOW_SECS: u64 = 60;
+const CIRCUIT_COOLDOWN_SECS: u64 = 120;
+const USER_CAUSED_ERROR_HINTS: [&str; 4] = ["cancel", "insufficient", "rejected by user", "denied"];
 
-        println!("TradingView bridge listening on port {}", BRIDGE_PORT);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
 
-        for mut request in server.incoming_requests() {
-            let url = request.url().to_string();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CircuitOutcome {
+    Success,
+    Failure,
+    /// Trade never reached the exchange (aborted, emit failed, app shutting down); frees the
+    /// half-open probe
+    NoVerdict,
+}
 
-            // CORS headers for browser extension
-            let cors_headers = vec![
-                tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], &b"*"[..]).unwrap(),
-                tiny_http::Header::from_bytes(&b"Access-Control-Allow-Methods"[..], &b"GET, POST, OPTIONS"[..]).unwrap(),
-                tiny_http::Header::from_bytes(&b"Access-Control-Allow-Headers"[..], &b"Content-Type"[..]).unwrap(),
-            ];
+struct CircuitBreaker {
+    state: CircuitState,
+    threshold: usize,
+    window: Duration,
+    cooldown: Duration,
+    /// Consecutive failures, oldest first, pruned to the window
+    failures: std::collections::VecDeque<Instant>,
+    opened_at: Option<Instant>,
+    /// Token of the half-open probe in flight; only its outcome decides the half-open state
+    probe: Option<u64>,
+    last_probe: u64,
+}
 
-            // Handle preflight OPTIONS request
-            if request.method() == &tiny_http::Method::Options {
-                let response = tiny_http::Response::empty(200).with_header(cors_headers[0].clone())
-                    .with_header(cors_headers[1].clone())
-                    .with_header(cors_headers[2].clone());
-                let _ = request.respond(response);
-                continue;
-            }
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitBreakerStatus {
+    state: CircuitState,
+    #[serde(rename = "consecutiveFailures")]
+    consecutive_failures: usize,
+    #[serde(rename = "retryAfterSecs")]
+    retry_after_secs: Option<u64>,
+}
 
-            // GET /settings - return current settings
-            if url == "/settings" && request.method() == &tiny_http::Method::Get {
-                let current_settings = settings.lock().unwrap().clone();
-                let json = serde_json::to_string(&current_settings).unwrap_or_else(|_| r#"{"risk":1,"leverage":25}"#.to_string());
-                let response = tiny_http::Response::from_string(json)
-                    .with_header(cors_headers[0].clone())
-                    .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
-                let _ = request.respond(response);
-                continue;
+impl CircuitBreaker {
+    fn new(threshold: usize, window: Duration, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            state: CircuitState::Closed,
+            threshold,
+            window,
+            cooldown,
+            failures: std::collections::VecDeque::new(),
+            opened_at: None,
+            probe: None,
+            last_probe: 0,
+        }
+    }
+
+    /// Whether a trade may go through now: Ok(Some(token)) when it is the half-open probe, which
+    /// has to be handed back to record. Err holds the time left until the next probe.
+    fn allow(&mut self, now: Instant) -> Result<Option<u64>, Duration> {
+        match self.state {
+            CircuitState::Closed => Ok(None),
+            CircuitState::Open => {
+                let elapsed = self.opened_at.map(|at| now.duration_since(at)).unwrap_or(self.cooldown);
+                if elapsed < self.cooldown {
+                    return Err(self.cooldown - elapsed);
+                }
+                self.state = CircuitState::HalfOpen;
+                Ok(Some(self.start_probe()))
             }
+            CircuitState::HalfOpen if self.probe.is_some() => Err(Duration::ZERO),
+            CircuitState::HalfOpen => Ok(Some(self.start_probe())),
+        }
+    }
 
-            if url == "/position" && request.method() == &tiny_http::Method::Post {
-                // Read body
-                let mut body = String::new();
-                if request.as_reader().read_to_string(&mut body).is_ok() {
-                    println!("Received position data: {}", body);
-                    if let Ok(position_data) = serde_json::from_str::<PositionData>(&body) {
-                        println!("Parsed position: {:?}", position_data);
-                        // Emit event to frontend
-                        match app_handle.emit("tradingview-position", position_data) {
-                            Ok(_) => println!("Event emitted successfully"),
-                            Err(e) => println!("Failed to emit event: {}", e),
-                        }
-                    } else {
-                        println!("Failed to parse position data");
-                    }
+    fn start_probe(&mut self) -> u64 {
+        self.last_probe += 1;
+        self.probe = Some(self.last_probe);
+        self.last_probe
+    }
+
+    /// Record a trade's outcome; `probe` is the token allow gave it, if any. While the circuit is
+    /// open or half-open only the probe's outcome counts: a late result of a trade let through
+    /// before it opened changes nothing.
+    fn record(&mut self, outcome: CircuitOutcome, probe: Option<u64>, now: Instant) {
+        let is_probe = probe.is_some() && probe == self.probe;
+        if is_probe {
+            self.probe = None;
+        } else if self.state != CircuitState::Closed {
+            return;
+        }
+        match outcome {
+            CircuitOutcome::Success => {
+                self.failures.clear();
+                self.state = CircuitState::Closed;
+                self.opened_at = None;
+            }
+            CircuitOutcome::Failure => {
+                self.failures.push_back(now);
+                while self.failures.front().is_some_and(|at| now.duration_since(*at) > self.window) {
+                    self.failures.pop_front();
                 }
+                // A failed probe re-opens immediately
+                if self.state == CircuitState::HalfOpen || self.failures.len() >= self.threshold {
+                    self.state = CircuitState::Open;
+                    self.opened_at = Some(now);
+                }
+            }
+            CircuitOutcome::NoVerdict => {}
+        }
+    }
 
-                let response = tiny_http::Response::from_string("OK")
-                    .with_header(cors_headers[0].clone());
-                let _ = request.respond(response);
-            } else if url == "/position-closed" && request.method() == &tiny_http::Method::Post {
-                // Emit close event to frontend
-                let _ = app_handle.emit("tradingview-position-closed", ());
-
-                let response = tiny_http::Response::from_string("OK")
-                    .with_header(cors_headers[0].clone());
-                let _ = request.respond(response);
-            } else if url == "/execute-trade" && request.method() == &tiny_http::Method::Post {
-                // Execute trade from extension - wait for actual result
-                let mut body = String::new();
-                if request.as_reader().read_to_string(&mut body).is_ok() {
-                    println!("Received trade request: {}", body);
-                    if let Ok(trade_request) = serde_json::from_str::<TradeRequest>(&body) {
-                        println!("Executing trade: {:?}", trade_request);
-
-                        // Create channel for this trade result
-                        let (tx, rx) = channel::<TradeResult>();
-
-                        // Store sender for frontend to use
-                        if let Some(sender_lock) = TRADE_RESULT_SENDER.get() {
-                            if let Ok(mut guard) = sender_lock.lock() {
-                                *guard = Some(tx);
-                            }
-                        } else {
-                            let _ = TRADE_RESULT_SENDER.set(Mutex::new(Some(tx)));
-                        }
-
-                        // Emit event to frontend to execute the trade
-                        match app_handle.emit("tradingview-execute-trade", trade_request) {
-                            Ok(_) => {
-                                println!("Trade execution event emitted, waiting for result...");
-
-                                // Wait for result with 60 second timeout (Drift on-chain txs can be slow)
-                                use std::time::Duration;
-                                match rx.recv_timeout(Duration::from_secs(60)) {
-                                    Ok(result) => {
-                                        println!("Trade result received: {:?}", result);
-                                        let response_body = if result.success {
-                                            "{\"success\":true}".to_string()
-                                        } else {
-                                            let error = result.error.unwrap_or_else(|| "Trade failed".to_string());
-                                            // Escape quotes in error message for JSON
-                                            let escaped = error.replace("\"", "\\\"");
-                                            format!("{{\"success\":false,\"error\":\"{}\"}}", escaped)
-                                        };
-                                        let response = tiny_http::Response::from_string(response_body)
-                                            .with_header(cors_headers[0].clone())
-                                            .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
-                                        let _ = request.respond(response);
-                                    }
-                                    Err(_) => {
-                                        println!("Trade result timeout");
-                                        let response = tiny_http::Response::from_string("{\"success\":false,\"error\":\"Trade execution timeout\"}")
-                                            .with_status_code(408)
-                                            .with_header(cors_headers[0].clone())
-                                            .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
-                                        let _ = request.respond(response);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                println!("Failed to emit trade event: {}", e);
-                                let response = tiny_http::Response::from_string(format!("{{\"success\":false,\"error\":\"{}\"}}", e))
-                                    .with_status_code(500)
-                                    .with_header(cors_headers[0].clone());
-                                let _ = request.respond(response);
-                            }
-                        }
-                    } else {
-                        println!("Failed to parse trade request");
-                        let response = tiny_http::Response::from_string("{\"success\":false,\"error\":\"Invalid request\"}")
-                            .with_status_code(400)
-                            .with_header(cors_headers[0].clone());
-                        let _ = request.respond(response);
-                    }
-                } else {
-                    let response = tiny_http::Response::from_string("{\"success\":false,\"error\":\"Failed to read body\"}")
-                        .with_status_code(400)
-                        .with_header(cors_headers[0].clone());
-                    let _ = request.respond(response);
+    fn reset(&mut self) {
+        self.state = CircuitState::Closed;
+        self.failures.clear();
+        self.opened_at = None;
+        self.probe = None;
+    }
+
+    fn status(&self, now: Instant) -> CircuitBreakerStatus {
+        CircuitBreakerStatus {
+            state: self.state,
+            consecutive_failures: self.failures.len(),
+            retry_after_secs: match (self.state, self.opened_at) {
+                (CircuitState::Open, Some(at)) => Some(self.cooldown.saturating_sub(now.duration_since(at)).as_secs()),
+                _ => None,
+            },
+        }
+    }
+}
+
+static CIRCUIT_BREAKER: std::sync::OnceLock<Mutex<CircuitBreaker>> = std::sync::OnceLock::new();
+
+fn circuit_breaker() -> &'static Mutex<CircuitBreaker> {
+    CIRCUIT_BREAKER.get_or_init(|| {
+        Mutex::new(CircuitBreaker::new(
+            CIRCUIT_FAILURE_THRESHOLD,
+            Duration::from_secs(CIRCUIT_FAILURE_WINDOW_SECS),
+            Duration::from_secs(CIRCUIT_COOLDOWN_SECS),
+        ))
+    })
+}
+
+fn is_user_caused_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    USER_CAUSED_ERROR_HINTS.iter().any(|hint| error.contains(hint))
+}
+
+/// Record a trade outcome (with its probe token, if it was the half-open probe) and emit
+/// circuit-breaker-changed when the state moves
+fn record_circuit_outcome<R: tauri::Runtime>(app: &tauri::AppHandle<R>, outcome: CircuitOutcome, probe: Option<u64>) {
+    let now = Instant::now();
+    let status = match circuit_breaker().lock() {
+        Ok(mut breaker) => {
+            let before = breaker.state;
+            breaker.record(outcome, probe, now);
+            (breaker.state != before).then(|| breaker.status(now))
+        }
+        Err(_) => None,
+    };
+    if let Some(status) = status {
+        println!("Circuit breaker now {:?}", status.state);
+        let _ = emit_change(app, "circuit-breaker-changed", status);
+    }
+}
+
+#[tauri::command]
+fn get_circuit_breaker() -> CircuitBreakerStatus {
+    let breaker = circuit_breaker().lock().unwrap_or_else(|e| e.into_inner());
+    breaker.status(Instant::now())
+}
+
+/// Close the circuit without waiting for the cool-down
+#[tauri::command]
+fn reset_circuit_breaker(app: tauri::AppHandle, window: tauri::Window) -> Result<CircuitBreakerStatus, String> {
+    authorize(&window, "reset_circuit_breaker", CommandTier::Trading)?;
+    let status = {
+        let mut breaker = circuit_breaker().lock().unwrap_or_else(|e| e.into_inner());
+        breaker.reset();
+        breaker.status(Instant::now())
+    };
+    let _ = emit_change(&app, "circuit-breaker-changed", status.clone());
+    Ok(status)
+}
+
+// ============ Extension Liveness ============
+// Extensions that send /hello are expected to POST /heartbeat periodically. Once one goes
+// quiet, tracked chart positions are treated as stale until it reconnects. Extensions that
+// never say hello are not tracked, so their positions are never marked stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ExtensionState {
+    Unknown,
+    Connected,
+    Disconnected,
+}
+
+struct ExtensionLiveness {
+    state: ExtensionState,
+    last_seen: Option<Instant>,
+}
+
+static EXTENSION_LIVENESS: std::sync::OnceLock<Mutex<ExtensionLiveness>> = std::sync::OnceLock::new();
+
+fn extension_liveness() -> &'static Mutex<ExtensionLiveness> {
+    EXTENSION_LIVENESS.get_or_init(|| {
+        Mutex::new(ExtensionLiveness {
+            state: ExtensionState::Unknown,
+            last_seen: None,
+        })
+    })
+}
+
+// Optional body of /hello
+#[derive(Debug, Default, Deserialize)]
+struct HelloRequest {
+    version: Option<String>,
+}
+
+/// Record a hello or heartbeat. Returns true when the extension should resend its positions.
+fn touch_extension<R: tauri::Runtime>(app: &tauri::AppHandle<R>, version: Option<String>) -> bool {
+    let mut liveness = match extension_liveness().lock() {
+        Ok(liveness) => liveness,
+        Err(_) => return true,
+    };
+    let previous = liveness.state;
+    liveness.state = ExtensionState::Connected;
+    liveness.last_seen = Some(Instant::now());
+    drop(liveness);
+
+    if previous != ExtensionState::Connected {
+        println!("Extension connected (version {})", version.as_deref().unwrap_or("unknown"));
+    }
+
+    if previous == ExtensionState::Disconnected {
+        println!("Extension reconnected");
+        let _ = emit_change(app, "extension-reconnected", serde_json::json!({ "resyncPositions": true }));
+    }
+    previous != ExtensionState::Connected
+}
+
+fn extension_state() -> ExtensionState {
+    extension_liveness().lock().map(|l| l.state).unwrap_or(ExtensionState::Unknown)
+}
+
+/// True while chart positions came from an extension that has since gone quiet
+fn chart_data_stale() -> bool {
+    extension_state() == ExtensionState::Disconnected
+}
+
+/// Watch for missed heartbeats and flag the extension as disconnected
+fn start_extension_monitor(app: tauri::AppHandle, settings: Arc<Mutex<BridgeSettings>>) {
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(1));
+        let timeout = Duration::from_secs(settings.lock().unwrap().extension_heartbeat_timeout_secs);
+        let timed_out = match extension_liveness().lock() {
+            Ok(mut liveness) => {
+                let expired = liveness.state == ExtensionState::Connected
+                    && liveness.last_seen.is_some_and(|seen| seen.elapsed() > timeout);
+                if expired {
+                    liveness.state = ExtensionState::Disconnected;
                 }
-            } else {
-                let response = tiny_http::Response::from_string("Not Found")
-                    .with_status_code(404)
-                    .with_header(cors_headers[0].clone());
-                let _ = request.respond(response);
+                expired
             }
+            Err(_) => false,
+        };
+        if timed_out {
+            let stale_assets: Vec<String> = tracked_positions()
+                .lock()
+                .map(|positions| positions.keys().cloned().collect())
+                .unwrap_or_default();
+            println!("Extension missed heartbeats, marking chart positions stale: {:?}", stale_assets);
+            let _ = emit_change(&app, "extension-disconnected", serde_json::json!({ "staleAssets": stale_assets }));
         }
     });
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    // Create shared settings state
-    let bridge_settings = Arc::new(Mutex::new(BridgeSettings::default()));
-    let bridge_settings_clone = bridge_settings.clone();
+// ============ Payload Field Naming ============
+// Extensions have sent both stopLoss and stop_loss over time; both parse via serde aliases.
+// Payloads mixing the two usually come from a half-migrated sender, so the policy decides.
+const MIXED_NAMING_POLICIES: [&str; 3] = ["allow", "warn", "reject"];
 
-    tauri::Builder::default()
-        .plugin(tauri_plugin_store::Builder::new().build())
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_clipboard_manager::init())
-        .plugin(tauri_plugin_fs::init())
-        .plugin(tauri_plugin_updater::Builder::new().build())
-        .plugin(tauri_plugin_process::init())
-        .manage(bridge_settings)
-        .setup(move |app| {
-            // Start the TradingView bridge server with shared settings
-            start_bridge_server(app.handle().clone(), bridge_settings_clone.clone());
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldNaming {
+    CamelCase,
+    SnakeCase,
+    Mixed,
+    /// Only single-word fields, nothing to tell
+    Neutral,
+}
+
+fn detect_field_naming(body: &str) -> FieldNaming {
+    let value = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(value) => value,
+        Err(_) => return FieldNaming::Neutral,
+    };
+    let keys = match value.as_object() {
+        Some(object) => object.keys(),
+        None => return FieldNaming::Neutral,
+    };
+    let (mut camel, mut snake) = (false, false);
+    for key in keys {
+        camel |= key.chars().any(|c| c.is_ascii_uppercase());
+        snake |= key.contains('_');
+    }
+    match (camel, snake) {
+        (true, true) => FieldNaming::Mixed,
+        (true, false) => FieldNaming::CamelCase,
+        (false, true) => FieldNaming::SnakeCase,
+        (false, false) => FieldNaming::Neutral,
+    }
+}
+
+/// Log the payload's naming convention and apply the mixed naming policy
+fn check_field_naming(body: &str, policy: &str, request_id: &str) -> Result<(), String> {
+    let naming = detect_field_naming(body);
+    if naming != FieldNaming::Neutral {
+        println!("[{}] Payload field naming: {:?}", request_id, naming);
+    }
+    if naming != FieldNaming::Mixed {
+        return Ok(());
+    }
+    match policy {
+        "reject" => Err("Payload mixes camelCase and snake_case field names".to_string()),
+        "warn" => {
+            println!("[{}] Warning: payload mixes camelCase and snake_case field names", request_id);
             Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+// ============ App Snapshot ============
+// get_app_snapshot returns everything the UI renders in one consistent read. Every state
+// change event carries a `seq` taken after the change was applied, so the frontend can drop
+// events with seq <= the snapshot's seq: their changes are already in the snapshot.
+static STATE_SEQ: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+fn next_state_seq() -> u64 {
+    STATE_SEQ.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+/// Emit a state change event with the next sequence number added to its payload.
+/// Payloads must serialize to JSON objects; anything else goes out without a seq.
+fn emit_change<R: tauri::Runtime, S: Serialize>(app: &tauri::AppHandle<R>, event: &str, payload: S) -> tauri::Result<()> {
+    let mut value = serde_json::to_value(payload)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert("seq".to_string(), next_state_seq().into());
+    }
+    app.emit(event, value)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppSnapshot {
+    seq: u64,
+    /// "running" or "shutting_down"
+    phase: String,
+    settings: BridgeSettings,
+    positions: std::collections::HashMap<String, PositionData>,
+    #[serde(rename = "pendingTrades")]
+    pending_trades: Vec<TradeRequest>,
+    extension: ExtensionState,
+    #[serde(rename = "chartDataStale")]
+    chart_data_stale: bool,
+    #[serde(rename = "circuitBreaker")]
+    circuit_breaker: CircuitBreakerStatus,
+    bridge: BridgeBinding,
+    #[serde(rename = "faultInjection")]
+    fault_injection: bool,
+}
+
+/// Consistent view of the app state. Locks are taken in this order, which no other code
+/// path reverses (most hold only one at a time): settings, tracked positions, pending trade,
+/// circuit breaker, extension liveness, bridge binding, fault injection.
+#[tauri::command]
+fn get_app_snapshot(state: tauri::State<Arc<Mutex<BridgeSettings>>>) -> AppSnapshot {
+    let settings = state.lock().unwrap_or_else(|e| e.into_inner());
+    let positions = tracked_positions().lock().unwrap_or_else(|e| e.into_inner());
+    let pending = pending_trades().lock().unwrap_or_else(|e| e.into_inner());
+    let breaker = circuit_breaker().lock().unwrap_or_else(|e| e.into_inner());
+    let liveness = extension_liveness().lock().unwrap_or_else(|e| e.into_inner());
+    let binding = bridge_binding().lock().unwrap_or_else(|e| e.into_inner());
+
+    AppSnapshot {
+        seq: STATE_SEQ.load(Ordering::SeqCst),
+        phase: if SHUTTING_DOWN.load(Ordering::SeqCst) { "shutting_down" } else { "running" }.to_string(),
+        settings: settings.clone(),
+        positions: positions.clone(),
+        pending_trades: {
+            let mut trades: Vec<&PendingTrade> = pending.values().collect();
+            trades.sort_by_key(|p| p.started_at);
+            trades.into_iter().map(|p| p.request.clone()).collect()
+        },
+        extension: liveness.state,
+        chart_data_stale: liveness.state == ExtensionState::Disconnected,
+        circuit_breaker: breaker.status(Instant::now()),
+        bridge: binding.clone(),
+        fault_injection: fault_injection().is_enabled(),
+    }
+}
+
+// ============ Strategy Tags ============
+const DEFAULT_STRATEGY: &str = "default";
+
+/// Strategy bucket for a trade: the configured tag it names, or "default" when untagged
+fn resolve_strategy(tag: Option<&str>, configured: &[String]) -> Result<String, String> {
+    match tag.map(str::trim).filter(|t| !t.is_empty()) {
+        None => Ok(DEFAULT_STRATEGY.to_string()),
+        Some(tag) if tag == DEFAULT_STRATEGY || configured.iter().any(|s| s == tag) => Ok(tag.to_string()),
+        Some(tag) => Err(format!("Unknown strategy '{}'", tag)),
+    }
+}
+
+// ============ Trade Deduplication ============
+// Double clicks and network retries: a repeated Idempotency-Key, or the same trade parameters
+// within duplicate_trade_window_secs, get the first trade's status instead of a second position.
+// Maps dedup key -> (trade id, expiry).
+static RECENT_TRADE_KEYS: std::sync::OnceLock<Mutex<std::collections::HashMap<String, (String, Instant)>>> = std::sync::OnceLock::new();
+
+fn recent_trade_keys() -> &'static Mutex<std::collections::HashMap<String, (String, Instant)>> {
+    RECENT_TRADE_KEYS.get_or_init(|| Mutex::new(std::collections::HashMap::new()))
+}
+
+/// Parameter dedup key: everything that decides what gets placed, including the asset the trade
+/// will execute on (the active settings asset)
+fn trade_dedup_key(trade: &TradeRequest, asset: &str) -> String {
+    format!(
+        "trade:{}|{}|{}|{}|{:?}|{}|{}|{:?}|{:?}",
+        asset,
+        trade.direction,
+        trade.entry,
+        trade.stop_loss,
+        trade.take_profit,
+        trade.risk,
+        trade.leverage,
+        trade.strategy,
+        trade.chart_context.as_ref().and_then(|context| context.symbol.as_deref()),
+    )
+}
+
+/// Claim the dedup key for a new trade, or return the id of the trade that already holds it.
+/// Idempotency keys are remembered as long as trade results are; parameter matches only for
+/// `window_secs` (0 turns parameter matching off). Ok holds the claimed key, for
+/// release_trade_key if the trade doesn't go ahead.
+fn claim_trade_key(idempotency_key: Option<&str>, trade: &TradeRequest, asset: &str, trade_id: &str, window_secs: u64) -> Result<Option<String>, String> {
+    let (key, window) = match idempotency_key {
+        Some(key) => (format!("key:{}", key), COMPLETED_TRADE_TTL_SECS),
+        None if window_secs > 0 => (trade_dedup_key(trade, asset), window_secs),
+        None => return Ok(None),
+    };
+
+    let now = Instant::now();
+    let mut keys = recent_trade_keys().lock().unwrap_or_else(|e| e.into_inner());
+    keys.retain(|_, (_, expires_at)| *expires_at > now);
+    if let Some((original, _)) = keys.get(&key) {
+        return Err(original.clone());
+    }
+    keys.insert(key.clone(), (trade_id.to_string(), now + Duration::from_secs(window)));
+    Ok(Some(key))
+}
+
+/// Give back a key claimed for `trade_id`, so a retry isn't reported as a duplicate of a trade
+/// that never went ahead
+fn release_trade_key(key: Option<&str>, trade_id: &str) {
+    if let Some(key) = key {
+        let mut keys = recent_trade_keys().lock().unwrap_or_else(|e| e.into_inner());
+        if keys.get(key).is_some_and(|(holder, _)| holder == trade_id) {
+            keys.remove(key);
+        }
+    }
+}
+
+// ============ Settings Updates ============
+/// Partial settings the extension may send to PUT /settings
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SettingsPatch {
+    risk: Option<f64>,
+    leverage: Option<u32>,
+    asset: Option<String>,
+}
+
+/// Settings with the patch applied, or every field that failed validation
+fn apply_settings_patch(current: &BridgeSettings, patch: SettingsPatch) -> Result<BridgeSettings, Vec<FieldError>> {
+    let mut errors = Vec::new();
+    let mut updated = current.clone();
+    if let Some(risk) = patch.risk {
+        if risk.is_finite() && risk > 0.0 && risk <= 100.0 {
+            updated.risk = risk;
+        } else {
+            errors.push(FieldError::new("risk", "must be greater than 0 and at most 100"));
+        }
+    }
+    if let Some(leverage) = patch.leverage {
+        if (1..=current.max_leverage).contains(&leverage) {
+            updated.leverage = leverage;
+        } else {
+            errors.push(FieldError::new("leverage", format!("must be between 1 and {}", current.max_leverage)));
+        }
+    }
+    if let Some(asset) = patch.asset {
+        if asset.trim().is_empty() {
+            errors.push(FieldError::new("asset", "must not be empty"));
+        } else {
+            updated.asset = resolve_asset(&asset);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(updated)
+    } else {
+        Err(errors)
+    }
+}
+
+// ============ Trade Validation ============
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    field: &'static str,
+    message: String,
+}
+
+impl FieldError {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        FieldError { field, message: message.into() }
+    }
+}
+
+/// Check a trade before it reaches the frontend, normalizing the direction to lowercase.
+/// Field names in errors are the JSON (camelCase) ones.
+fn validate_trade_request(trade: &TradeRequest, max_leverage: u32) -> Result<TradeRequest, Vec<FieldError>> {
+    let mut errors = Vec::new();
+    let direction = trade.direction.trim().to_ascii_lowercase();
+    let is_long = match direction.as_str() {
+        "long" => Some(true),
+        "short" => Some(false),
+        _ => {
+            errors.push(FieldError::new("direction", format!("must be long or short, got '{}'", trade.direction)));
+            None
+        }
+    };
+
+    let entry_ok = trade.entry.is_finite() && trade.entry > 0.0;
+    if !entry_ok {
+        errors.push(FieldError::new("entry", "must be a positive number"));
+    }
+    let stop_ok = trade.stop_loss.is_finite() && trade.stop_loss > 0.0;
+    if !stop_ok {
+        errors.push(FieldError::new("stopLoss", "must be a positive number"));
+    }
+    if let (Some(is_long), true, true) = (is_long, entry_ok, stop_ok) {
+        if is_long && trade.stop_loss >= trade.entry {
+            errors.push(FieldError::new("stopLoss", "must be below entry for a long"));
+        } else if !is_long && trade.stop_loss <= trade.entry {
+            errors.push(FieldError::new("stopLoss", "must be above entry for a short"));
+        }
+    }
+    if let Some(take_profit) = trade.take_profit {
+        if !take_profit.is_finite() || take_profit <= 0.0 {
+            errors.push(FieldError::new("takeProfit", "must be a positive number"));
+        } else if let (Some(is_long), true) = (is_long, entry_ok) {
+            if is_long && take_profit <= trade.entry {
+                errors.push(FieldError::new("takeProfit", "must be above entry for a long"));
+            } else if !is_long && take_profit >= trade.entry {
+                errors.push(FieldError::new("takeProfit", "must be below entry for a short"));
+            }
+        }
+    }
+
+    if !trade.risk.is_finite() || trade.risk <= 0.0 || trade.risk > 100.0 {
+        errors.push(FieldError::new("risk", "must be greater than 0 and at most 100"));
+    }
+    if trade.leverage < 1 || trade.leverage > max_leverage {
+        errors.push(FieldError::new("leverage", format!("must be between 1 and {}", max_leverage)));
+    }
+
+    if errors.is_empty() {
+        Ok(TradeRequest { direction, ..trade.clone() })
+    } else {
+        Err(errors)
+    }
+}
+
+/// "field message; field message" for logs and single-line errors
+fn summarize_field_errors(errors: &[FieldError]) -> String {
+    errors.iter().map(|e| format!("{} {}", e.field, e.message)).collect::<Vec<_>>().join("; ")
+}
+
+// ============ Quick Trade ============
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickTradeProposal {
+    asset: String,
+    trade: TradeRequest,
+    /// "chart_position" when levels came from the tracked position tool, "last_price" otherwise
+    source: String,
+    /// Fields that were filled from defaults rather than chart levels
+    #[serde(rename = "defaultedFields")]
+    defaulted_fields: Vec<String>,
+    /// True whenever anything was defaulted; the UI must confirm such trades and never auto-execute them
+    #[serde(rename = "requiresConfirmation")]
+    requires_confirmation: bool,
+    /// Levels came from a chart position recorded before the extension disconnected
+    #[serde(rename = "staleChartData")]
+    stale_chart_data: bool,
+}
+
+/// Assemble a trade for the active asset from the tracked chart position, or from the
+/// last known price and the configured default stop distance. Never executes anything.
+#[tauri::command]
+fn quick_trade(window: tauri::Window, state: tauri::State<Arc<Mutex<BridgeSettings>>>, direction: String) -> Result<QuickTradeProposal, String> {
+    authorize(&window, "quick_trade", CommandTier::Trading)?;
+    let settings = state.lock().unwrap().clone();
+    propose_quick_trade(&settings, &direction)
+}
+
+fn propose_quick_trade(settings: &BridgeSettings, direction: &str) -> Result<QuickTradeProposal, String> {
+    let direction = direction.trim().to_lowercase();
+    if direction != "long" && direction != "short" {
+        return Err(format!("Invalid direction '{}', expected long or short", direction));
+    }
+    let tracked = tracked_positions()
+        .lock()
+        .ok()
+        .and_then(|positions| positions.get(&settings.asset).cloned())
+        .filter(|position| position.direction.eq_ignore_ascii_case(&direction));
+
+    let mut defaulted_fields = Vec::new();
+    let chart_context = tracked.as_ref().and_then(|position| position.chart_context.clone());
+    let (entry, stop_loss, take_profit, source) = match tracked {
+        Some(position) => (position.entry, position.stop_loss, position.take_profit, "chart_position"),
+        None => {
+            if settings.price <= 0.0 {
+                return Err(format!("No chart position or last price known for {}", settings.asset));
+            }
+            let distance = settings.price * settings.quick_trade_stop_pct / 100.0;
+            let stop_loss = if direction == "long" { settings.price - distance } else { settings.price + distance };
+            defaulted_fields.push("entry".to_string());
+            defaulted_fields.push("stopLoss".to_string());
+            (settings.price, stop_loss, None, "last_price")
+        }
+    };
+
+    // The same checks a bridge trade gets, before anything reaches the confirmation UI
+    let trade = TradeRequest {
+        direction,
+        entry,
+        stop_loss,
+        take_profit,
+        risk: settings.risk,
+        leverage: settings.leverage,
+        chart_context,
+        strategy: None,
+        trade_id: None,
+    };
+    let trade = validate_trade_request(&trade, settings.max_leverage)
+        .map_err(|errors| format!("Invalid trade: {}", summarize_field_errors(&errors)))?;
+
+    let stale_chart_data = source == "chart_position" && chart_data_stale();
+    let requires_confirmation = !defaulted_fields.is_empty() || stale_chart_data;
+    Ok(QuickTradeProposal {
+        asset: settings.asset.clone(),
+        trade,
+        source: source.to_string(),
+        defaulted_fields,
+        requires_confirmation,
+        stale_chart_data,
+    })
+}
+
+// ============ Conditional GET ============
+fn request_header<'a>(request: &'a tiny_http::Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+// ============ Bridge Responses ============
+/// JSON body for bridge endpoints: `success`, `error`/`code` on failure, and any endpoint
+/// data flattened alongside
+#[derive(Debug, Serialize)]
+struct BridgeResponse<T: Serialize = serde_json::Value> {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(flatten)]
+    data: Option<T>,
+}
+
+impl<T: Serialize> BridgeResponse<T> {
+    fn ok(data: T) -> Self {
+        BridgeResponse { success: true, error: None, code: None, data: Some(data) }
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            serde_json::json!({ "success": false, "error": e.to_string(), "code": "internal_error" }).to_string()
         })
-        .invoke_handler(tauri::generate_handler![
-            keychain_save,
-            keychain_load,
-            keychain_delete,
-            keychain_has_password,
-            update_bridge_settings,
-            report_trade_result,
-            check_biometric_available,
-            authenticate_biometric,
-            http_get,
-            http_post
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+    }
+
+    /// JSON response with the given status
+    fn into_http(self, status: u16) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+        tiny_http::Response::from_string(self.to_json())
+            .with_status_code(status)
+            .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap())
+    }
+}
+
+impl BridgeResponse {
+    fn success() -> Self {
+        BridgeResponse { success: true, error: None, code: None, data: None }
+    }
+
+    fn error(code: &'static str, error: impl Into<String>) -> Self {
+        BridgeResponse { success: false, error: Some(error.into()), code: Some(code), data: None }
+    }
+
+    fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+// Schema-only shapes of BridgeResponse, published under /v1/schema/response-*
+/// Successful bridge response; endpoint data is flattened alongside `success`
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct BridgeSuccessEnvelope {
+    success: bool,
+}
+
+/// Failed bridge response; some errors carry extra fields (e.g. `tradeId`) alongside
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct BridgeErrorEnvelope {
+    success: bool,
+    error: String,
+    /// Stable machine-readable error code, e.g. "validation_failed"
+    code: String,
+}
+
+// Methods each bridge path answers to, for 405s
+const BRIDGE_ROUTES: &[(&str, &str)] = &[
+    ("/settings", "GET, HEAD, PUT, POST"),
+    ("/last-error", "GET"),
+    ("/health", "GET, HEAD"),
+    ("/capabilities", "GET"),
+    ("/position", "POST"),
+    ("/hello", "POST"),
+    ("/heartbeat", "POST"),
+    ("/positions", "GET"),
+    ("/position-closed", "POST"),
+    ("/execute-trade", "POST"),
+    ("/cancel-trade", "POST"),
+];
+
+fn allowed_methods(url: &str) -> Option<&'static str> {
+    let path = url.split('?').next().unwrap_or(url);
+    if path.starts_with("/v1/schema/") || path.starts_with("/trade-status/") {
+        return Some("GET");
+    }
+    BRIDGE_ROUTES.iter().find(|(route, _)| *route == path).map(|(_, methods)| *methods)
+}
+
+// ============ Status Page ============
+// Shown to browsers that open the bridge URL; API clients get JSON errors instead.
+// Deliberately shows nothing about the account, positions or settings.
+const STATUS_PAGE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Hyperliquid Trader bridge</title></head>
+<body style="font-family: sans-serif; max-width: 36em; margin: 3em auto;">
+<h1>Hyperliquid Trader bridge</h1>
+<p>{{message}}</p>
+<ul>
+<li>App version: {{version}}</li>
+<li>Bridge API version: {{api_version}}</li>
+<li>Status: {{status}}</li>
+</ul>
+<p><a href="{{docs_url}}">Documentation</a></p>
+</body>
+</html>
+"#;
+const DOCS_URL: &str = "https://github.com/DobriVasilev/hyperliquid-trader";
+
+/// Browsers list text/html explicitly; API clients send */* or application/json
+fn wants_html(request: &tiny_http::Request) -> bool {
+    request_header(request, "Accept").is_some_and(|accept| {
+        accept.split(',').any(|media| {
+            let mut parts = media.split(';').map(str::trim);
+            let name = parts.next().unwrap_or("");
+            // "text/html;q=0" explicitly refuses it
+            let refused = parts.any(|param| param.strip_prefix("q=").and_then(|q| q.trim().parse::<f32>().ok()) == Some(0.0));
+            name.eq_ignore_ascii_case("text/html") && !refused
+        })
+    })
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_status_page(found: bool) -> String {
+    let message = if found {
+        "The app is running and the TradingView bridge is listening.".to_string()
+    } else {
+        "The app is running, but there is no page at this address.".to_string()
+    };
+    let status = if SHUTTING_DOWN.load(Ordering::SeqCst) {
+        "shutting down"
+    } else if fault_injection().is_enabled() {
+        "running (fault injection active)"
+    } else {
+        "running"
+    };
+    STATUS_PAGE_TEMPLATE
+        .replace("{{message}}", &html_escape(&message))
+        .replace("{{version}}", &html_escape(env!("CARGO_PKG_VERSION")))
+        .replace("{{api_version}}", BRIDGE_API_VERSION)
+        .replace("{{status}}", status)
+        .replace("{{docs_url}}", DOCS_URL)
+}
+
+// ============ Request Bodies ============
+const MAX_BODY_BYTES: usize = 64 * 1024;
+// Slow-trickle bodies are cut off after this long
+const BODY_READ_DEADLINE: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+enum BodyError {
+    TooLarge,
+    TimedOut,
+    Io(std::io::Error),
+}
+
+impl BodyError {
+    fn into_response(self) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+        match self {
+            BodyError::TooLarge => BridgeResponse::error("payload_too_large", format!("Request body is over {} bytes", MAX_BODY_BYTES)).into_http(413),
+            BodyError::TimedOut => BridgeResponse::error("body_timeout", "Request body not received in time").into_http(408),
+            BodyError::Io(e) => BridgeResponse::error("invalid_request", format!("Failed to read body: {}", e)).into_http(400),
+        }
+    }
+}
+
+// Outcome of reading a request body
+enum BodyRead {
+    Complete(tiny_http::Request, String),
+    Rejected(tiny_http::Request, BodyError),
+    /// The client didn't finish within the deadline. The request stays with the reader thread,
+    /// which answers 408 itself if the client ever sends more or hangs up.
+    Abandoned,
+}
+
+/// Read a request body of at most MAX_BODY_BYTES within `deadline`. tiny_http has no socket
+/// read timeout, so the read runs on a helper thread and a client that stalls completely
+/// costs that thread, not a bridge worker (see MAX_BRIDGE_WORKERS).
+fn read_body(mut request: tiny_http::Request, deadline: Duration, request_id: &str, allow_origin: Option<tiny_http::Header>) -> BodyRead {
+    if request.body_length().is_some_and(|len| len > MAX_BODY_BYTES) {
+        return BodyRead::Rejected(request, BodyError::TooLarge);
+    }
+
+    let (tx, rx) = channel();
+    let request_id = request_id.to_string();
+    let started = Instant::now();
+    thread::spawn(move || {
+        let body = read_body_until(&mut request, started + deadline);
+        if let Err(std::sync::mpsc::SendError((request, _))) = tx.send((request, body)) {
+            println!("[{}] Late request body, answering 408", request_id);
+            let _ = respond_traced(request, BodyError::TimedOut.into_response().with_allow_origin(&allow_origin), &request_id);
+        }
+    });
+    match rx.recv_timeout(deadline) {
+        Ok((request, Ok(body))) => BodyRead::Complete(request, body),
+        Ok((request, Err(e))) => BodyRead::Rejected(request, e),
+        Err(_) => BodyRead::Abandoned,
+    }
+}
+
+fn read_body_until(request: &mut tiny_http::Request, deadline: Instant) -> Result<String, BodyError> {
+    let expected = request.body_length();
+    let reader = request.as_reader();
+    let mut body = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = reader.read(&mut chunk).map_err(BodyError::Io)?;
+        if read == 0 {
+            break;
+        }
+        if body.len() + read > MAX_BODY_BYTES {
+            return Err(BodyError::TooLarge);
+        }
+        body.extend_from_slice(&chunk[..read]);
+        if Instant::now() > deadline {
+            return Err(BodyError::TimedOut);
+        }
+    }
+    // tiny_http ends a Content-Length body quietly when the client hangs up early
+    if expected.is_some_and(|len| body.len() < len) {
+        let truncated = std::io::Error::new(std::io::ErrorKind::UnexpectedEof, format!("got {} of {} bytes", body.len(), expected.unwrap_or(0)));
+        return Err(BodyError::Io(truncated));
+    }
+    String::from_utf8(body).map_err(|e| BodyError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+}
+
+// ============ Response Compression ============
+// Larger JSON bodies are gzipped for clients that ask for it; small ones aren't worth the CPU
+const COMPRESSION_MIN_BYTES: usize = 1024;
+
+fn accepts_gzip(request: &tiny_http::Request) -> bool {
+    request_header(request, "Accept-Encoding").is_some_and(|accept| {
+        accept.split(',').any(|coding| {
+            let mut parts = coding.split(';').map(str::trim);
+            let name = parts.next().unwrap_or("");
+            // "gzip;q=0" explicitly refuses it
+            let refused = parts.any(|param| param.strip_prefix("q=").and_then(|q| q.trim().parse::<f32>().ok()) == Some(0.0));
+            (name.eq_ignore_ascii_case("gzip") || name == "*") && !refused
+        })
+    })
+}
+
+/// Response for a body that may be large: gzip-encoded when the client accepts it and the
+/// body is over the threshold. Always varies on Accept-Encoding.
+fn compressible_response(request: &tiny_http::Request, body: String) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    use std::io::Write;
+
+    let vary = tiny_http::Header::from_bytes(&b"Vary"[..], &b"Accept-Encoding"[..]).unwrap();
+    if body.len() >= COMPRESSION_MIN_BYTES && accepts_gzip(request) {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        if let Ok(compressed) = encoder.write_all(body.as_bytes()).and_then(|_| encoder.finish()) {
+            return tiny_http::Response::from_data(compressed)
+                .with_header(tiny_http::Header::from_bytes(&b"Content-Encoding"[..], &b"gzip"[..]).unwrap())
+                .with_header(vary);
+        }
+    }
+    tiny_http::Response::from_string(body).with_header(vary)
+}
+
+// ============ Request Tracing ============
+// X-Request-Id lets extension and app logs be lined up; generated when the caller sends none
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+fn request_id_for(request: &tiny_http::Request) -> String {
+    request_header(request, "X-Request-Id")
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.chars().all(|c| c.is_ascii_graphic()))
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+/// Respond with the request id echoed back (and readable by the extension)
+fn respond_traced<R: std::io::Read>(request: tiny_http::Request, response: tiny_http::Response<R>, request_id: &str) -> std::io::Result<()> {
+    let line = format!("{} {} -> {}", request.method(), request.url(), response.status_code().0);
+    bridge_log(request_id, None, line);
+    let response = response
+        .with_header(tiny_http::Header::from_bytes(&b"X-Request-Id"[..], request_id.as_bytes()).unwrap())
+        .with_header(tiny_http::Header::from_bytes(&b"Access-Control-Expose-Headers"[..], &b"X-Request-Id"[..]).unwrap())
+        .with_header(tiny_http::Header::from_bytes(&b"Vary"[..], &b"Origin"[..]).unwrap());
+    request.respond(response)
+}
+
+// ============ Bridge Log ============
+// Ring buffer of what the bridge did per request, for get_bridge_log
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeLogEntry {
+    #[serde(rename = "requestId")]
+    request_id: String,
+    #[serde(rename = "tradeId", skip_serializing_if = "Option::is_none")]
+    trade_id: Option<String>,
+    message: String,
+    /// Unix time in milliseconds
+    timestamp: u64,
+}
+
+static BRIDGE_LOG: std::sync::OnceLock<Mutex<std::collections::VecDeque<BridgeLogEntry>>> = std::sync::OnceLock::new();
+
+fn bridge_log(request_id: &str, trade_id: Option<&str>, message: impl Into<String>) {
+    let lock = BRIDGE_LOG.get_or_init(|| Mutex::new(std::collections::VecDeque::with_capacity(MAX_BRIDGE_LOG_ENTRIES)));
+    if let Ok(mut log) = lock.lock() {
+        if log.len() == MAX_BRIDGE_LOG_ENTRIES {
+            log.pop_front();
+        }
+        log.push_back(BridgeLogEntry {
+            request_id: request_id.to_string(),
+            trade_id: trade_id.map(|id| id.to_string()),
+            message: message.into(),
+            timestamp: unix_millis(),
+        });
+    }
+}
+
+/// Bridge log entries, oldest first; only those of one request when `request_id` is given
+#[tauri::command]
+fn get_bridge_log(request_id: Option<String>) -> Vec<BridgeLogEntry> {
+    BRIDGE_LOG
+        .get()
+        .and_then(|lock| lock.lock().ok())
+        .map(|log| {
+            log.iter()
+                .filter(|entry| request_id.as_deref().is_none_or(|id| entry.request_id == id))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Strong ETag for a response body
+fn body_etag(body: &str) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Whether the client's cached copy is still current. If-None-Match wins over If-Modified-Since:
+/// HTTP dates only have whole seconds, so two changes within a second share a Last-Modified and
+/// only the ETag tells them apart.
+fn is_not_modified(request: &tiny_http::Request, etag: &str, modified_at: u64) -> bool {
+    if let Some(if_none_match) = request_header(request, "If-None-Match") {
+        return if_none_match.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*");
+    }
+    request_header(request, "If-Modified-Since")
+        .and_then(|value| httpdate::parse_http_date(value).ok())
+        .and_then(|since| since.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since| modified_at <= since.as_secs())
+        .unwrap_or(false)
+}
+
+/// Value of a query string parameter in a request URL
+fn query_param<'a>(url: &'a str, key: &str) -> Option<&'a str> {
+    let query = url.split_once('?')?.1;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+// ============ Trade Countdown ============
+// Running countdowns by trade id, true once aborted
+static ACTIVE_COUNTDOWNS: std::sync::OnceLock<Mutex<std::collections::HashMap<String, bool>>> = std::sync::OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+struct TradeCountdownEvent {
+    #[serde(rename = "tradeId")]
+    trade_id: String,
+    remaining: u64,
+    trade: TradeRequest,
+}
+
+fn active_countdowns() -> &'static Mutex<std::collections::HashMap<String, bool>> {
+    ACTIVE_COUNTDOWNS.get_or_init(|| Mutex::new(std::collections::HashMap::new()))
+}
+
+/// Count down before a trade is emitted for execution, emitting `trade-countdown` each second.
+/// Returns false if the countdown was aborted (or the app started shutting down).
+fn run_trade_countdown<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, trade_id: &str, trade: &TradeRequest, secs: u64) -> bool {
+    let trade_id = trade_id.to_string();
+    if let Ok(mut guard) = active_countdowns().lock() {
+        guard.insert(trade_id.clone(), false);
+    }
+    println!("Trade countdown {} started ({}s)", trade_id, secs);
+
+    // Native notification, so the countdown is noticed with the window in the background
+    let summary = format!(
+        "{} entry {} / stop {} - open the app to abort",
+        trade.direction.to_uppercase(),
+        trade.entry,
+        trade.stop_loss
+    );
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(format!("Executing trade in {}s", secs))
+        .body(summary)
+        .show()
+    {
+        eprintln!("Failed to show countdown notification: {}", e);
+    }
+
+    let is_aborted = || {
+        SHUTTING_DOWN.load(Ordering::SeqCst)
+            || active_countdowns().lock().map(|g| g.get(&trade_id).copied().unwrap_or(true)).unwrap_or(true)
+    };
+
+    let mut completed = true;
+    'countdown: for remaining in (1..=secs).rev() {
+        let _ = emit_change(app_handle, "trade-countdown", TradeCountdownEvent {
+            trade_id: trade_id.clone(),
+            remaining,
+            trade: trade.clone(),
+        });
+        for _ in 0..10 {
+            if is_aborted() {
+                completed = false;
+                break 'countdown;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+    if completed && is_aborted() {
+        completed = false;
+    }
+
+    if let Ok(mut guard) = active_countdowns().lock() {
+        guard.remove(&trade_id);
+    }
+    if !completed {
+        println!("Trade countdown {} aborted", trade_id);
+        record_bridge_error("trade_aborted", "Trade aborted during countdown", Some(&trade_id), None);
+        let _ = emit_change(app_handle, "trade-countdown-aborted", serde_json::json!({ "tradeId": trade_id }));
+    }
+    completed
+}
+
+/// Abort a running trade countdown so the trade is never executed.
+/// Ok(false) means no countdown is running for the trade (it already finished or never started).
+#[tauri::command]
+fn abort_countdown<R: tauri::Runtime>(window: tauri::Window<R>, trade_id: String) -> Result<bool, String> {
+    authorize(&window, "abort_countdown", CommandTier::Trading)?;
+    Ok(abort_active_countdown(&trade_id))
+}
+
+fn abort_active_countdown(trade_id: &str) -> bool {
+    match active_countdowns().lock() {
+        Ok(mut guard) => match guard.get_mut(trade_id) {
+            Some(aborted) => {
+                *aborted = true;
+                true
+            }
+            None => false,
+        },
+        Err(_) => false,
+    }
+}
+
+// ============ Crash Reports ============
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Unix time in milliseconds
+    timestamp: u64,
+    version: String,
+    thread: String,
+    message: String,
+    location: Option<String>,
+    backtrace: String,
+}
+
+/// Write every panic (any thread, webview or not) to a crash file in the app data dir
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Unknown panic".to_string());
+        let report = CrashReport {
+            timestamp: unix_millis(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            thread: thread::current().name().unwrap_or("unnamed").to_string(),
+            message,
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        };
+        if let Ok(json) = serde_json::to_string_pretty(&report) {
+            let _ = std::fs::write(get_app_data_dir().join(CRASH_REPORT_FILE), json);
+        }
+        default_hook(info);
+    }));
+}
+
+/// Crash report left by the last panic, if the user hasn't dismissed it
+#[tauri::command]
+fn get_last_crash_report() -> Option<CrashReport> {
+    let contents = std::fs::read_to_string(get_app_data_dir().join(CRASH_REPORT_FILE)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+#[tauri::command]
+fn dismiss_crash_report(window: tauri::Window) -> Result<(), String> {
+    authorize(&window, "dismiss_crash_report", CommandTier::Admin)?;
+    let _ = std::fs::remove_file(get_app_data_dir().join(CRASH_REPORT_FILE));
+    Ok(())
+}
+
+// ============ Graceful Shutdown ============
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+// Written when the app exits with a trade still unresolved, so the next start can warn about it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingTradeMarker {
+    requests: Vec<TradeRequest>,
+    #[serde(rename = "interruptedAt")]
+    interrupted_at: u64,
+}
+
+fn has_pending_trade() -> bool {
+    pending_trades().lock().map(|guard| !guard.is_empty()).unwrap_or(false)
+}
+
+/// Fail the pending trades (if any) and persist a marker for the next startup
+fn finish_shutdown() {
+    let mut pending = match pending_trades().lock() {
+        Ok(mut guard) => interrupt_pending_trades(&mut guard),
+        Err(_) => Vec::new(),
+    };
+    if !pending.is_empty() {
+        pending.sort_by_key(|(started_at, _)| *started_at);
+        let marker = PendingTradeMarker {
+            requests: pending.into_iter().map(|(_, request)| request).collect(),
+            interrupted_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+        let path = get_app_data_dir().join(PENDING_TRADE_MARKER);
+        match serde_json::to_string(&marker).map(|json| std::fs::write(&path, json)) {
+            Ok(Ok(())) => println!("Shutdown with unresolved trade, marker written to {}", path.display()),
+            Ok(Err(e)) => eprintln!("Failed to write pending trade marker: {}", e),
+            Err(e) => eprintln!("Failed to serialize pending trade marker: {}", e),
+        }
+
+        // Give the bridge thread a moment to write the error response
+        thread::sleep(Duration::from_millis(200));
+    }
+    println!("Shutdown complete");
+}
+
+/// Resolve every pending trade as interrupted, so its HTTP request is answered and /trade-status
+/// reports "interrupted". Returns when each started and its request, trade id included.
+fn interrupt_pending_trades(pending: &mut std::collections::HashMap<String, PendingTrade>) -> Vec<(Instant, TradeRequest)> {
+    let trades: Vec<(String, Instant, TradeRequest)> = pending
+        .iter()
+        .map(|(trade_id, trade)| (trade_id.clone(), trade.started_at, trade.request.clone()))
+        .collect();
+    trades
+        .into_iter()
+        .map(|(trade_id, started_at, mut request)| {
+            let interrupted = TradeResult {
+                success: false,
+                error: Some("App shutting down".to_string()),
+                resolution: TradeResolution::Interrupted,
+            };
+            let _ = resolve_locked(pending, &trade_id, interrupted);
+            request.trade_id = Some(trade_id);
+            (started_at, request)
+        })
+        .collect()
+}
+
+/// Handle an exit request: hold the app open while a trade is pending, up to the configured max wait
+fn handle_exit_requested(app_handle: &tauri::AppHandle, api: &tauri::ExitRequestApi) {
+    if !has_pending_trade() || SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        // Nothing to wait for, or a second quit while already waiting (forced quit)
+        return;
+    }
+
+    api.prevent_exit();
+    let max_wait = app_handle
+        .try_state::<Arc<Mutex<BridgeSettings>>>()
+        .map(|settings| settings.lock().unwrap().shutdown_max_wait_secs)
+        .unwrap_or(30);
+    println!("Trade pending, delaying shutdown up to {}s", max_wait);
+    let _ = emit_change(app_handle, "app-shutting-down", serde_json::json!({ "maxWaitSecs": max_wait }));
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(max_wait);
+        while has_pending_trade() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(100));
+        }
+        app_handle.exit(0);
+    });
+}
+
+/// Read and clear the marker left by a shutdown that interrupted a trade
+#[tauri::command]
+fn take_pending_trade_marker<R: tauri::Runtime>(window: tauri::Window<R>) -> Result<Option<PendingTradeMarker>, String> {
+    authorize(&window, "take_pending_trade_marker", CommandTier::Admin)?;
+    let path = get_app_data_dir().join(PENDING_TRADE_MARKER);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(None),
+    };
+    let _ = std::fs::remove_file(&path);
+    Ok(serde_json::from_str(&contents).ok())
+}
+
+// ============ HTTP Proxy for CORS bypass ============
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpResponse {
+    success: bool,
+    data: Option<String>,
+    error: Option<String>,
+    status: u16,
+}
+
+/// HTTP GET request - bypasses CORS by making request from Rust
+#[tauri::command]
+async fn http_get<R: tauri::Runtime>(window: tauri::Window<R>, url: String) -> HttpResponse {
+    if let Err(e) = authorize(&window, "http_get", CommandTier::Trading) {
+        return HttpResponse { success: false, data: None, error: Some(e), status: 403 };
+    }
+    match reqwest::get(&url).await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            match response.text().await {
+                Ok(text) => HttpResponse {
+                    success: status >= 200 && status < 300,
+                    data: Some(text),
+                    error: None,
+                    status,
+                },
+                Err(e) => HttpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Failed to read response: {}", e)),
+                    status,
+                },
+            }
+        }
+        Err(e) => HttpResponse {
+            success: false,
+            data: None,
+            error: Some(format!("Request failed: {}", e)),
+            status: 0,
+        },
+    }
+}
+
+/// HTTP POST request - bypasses CORS
+#[tauri::command]
+async fn http_post(window: tauri::Window, url: String, body: String) -> HttpResponse {
+    if let Err(e) = authorize(&window, "http_post", CommandTier::Trading) {
+        return HttpResponse { success: false, data: None, error: Some(e), status: 403 };
+    }
+    let client = reqwest::Client::new();
+    match client.post(&url)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            match response.text().await {
+                Ok(text) => HttpResponse {
+                    success: status >= 200 && status < 300,
+                    data: Some(text),
+                    error: None,
+                    status,
+                },
+                Err(e) => HttpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Failed to read response: {}", e)),
+                    status,
+                },
+            }
+        }
+        Err(e) => HttpResponse {
+            success: false,
+            data: None,
+            error: Some(format!("Request failed: {}", e)),
+            status: 0,
+        },
+    }
+}
+
+// ============ Bridge Payload Schemas ============
+const SCHEMA_NAMES: [&str; 6] = [
+    "trade-request",
+    "position-data",
+    "bridge-settings",
+    "trade-result",
+    "response-success",
+    "response-error",
+];
+
+/// JSON Schema for a bridge payload, tagged with the bridge API version
+fn bridge_schema(name: &str) -> Option<serde_json::Value> {
+    let schema = match name {
+        "trade-request" => schemars::schema_for!(TradeRequest),
+        "position-data" => schemars::schema_for!(PositionData),
+        "bridge-settings" => schemars::schema_for!(BridgeSettings),
+        "trade-result" => schemars::schema_for!(TradeResult),
+        "response-success" => schemars::schema_for!(BridgeSuccessEnvelope),
+        "response-error" => schemars::schema_for!(BridgeErrorEnvelope),
+        _ => return None,
+    };
+
+    let mut value = serde_json::to_value(schema).ok()?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("$id".to_string(), format!("hyperliquid-trader/bridge/v{}/{}", BRIDGE_API_VERSION, name).into());
+        obj.insert("x-bridge-api-version".to_string(), BRIDGE_API_VERSION.into());
+    }
+    Some(value)
+}
+
+// ============ Fault Injection (development builds only) ============
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FaultInjectionConfig {
+    /// Probability (0-1) of delaying a response by `delay_ms`
+    delay_probability: f64,
+    delay_ms: u64,
+    /// Probability of answering with a 500
+    error_probability: f64,
+    /// Probability of closing the connection without a response
+    drop_probability: f64,
+    /// Probability of answering with a truncated JSON body
+    malformed_probability: f64,
+    /// Answer every /execute-trade with the timeout error
+    force_trade_timeout: bool,
+    /// Paths the faults apply to (e.g. "/settings"); empty means all
+    targets: Vec<String>,
+}
+
+impl FaultInjectionConfig {
+    fn is_enabled(&self) -> bool {
+        self.delay_probability > 0.0
+            || self.error_probability > 0.0
+            || self.drop_probability > 0.0
+            || self.malformed_probability > 0.0
+            || self.force_trade_timeout
+    }
+
+    fn applies_to(&self, url: &str) -> bool {
+        let path = url.split('?').next().unwrap_or(url);
+        self.targets.is_empty() || self.targets.iter().any(|t| t == path)
+    }
+}
+
+static FAULT_INJECTION: std::sync::OnceLock<Mutex<FaultInjectionConfig>> = std::sync::OnceLock::new();
+
+fn fault_injection() -> FaultInjectionConfig {
+    FAULT_INJECTION
+        .get_or_init(|| Mutex::new(FaultInjectionConfig::default()))
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+fn roll_fault(probability: f64) -> bool {
+    probability > 0.0 && rand::random::<f64>() < probability
+}
+
+/// Configure bridge fault injection. Only available in development builds;
+/// while enabled, trades from the bridge are answered as dry runs and never executed.
+#[tauri::command]
+fn set_fault_injection(window: tauri::Window, config: FaultInjectionConfig) -> Result<FaultInjectionConfig, String> {
+    authorize(&window, "set_fault_injection", CommandTier::Admin)?;
+    if !cfg!(debug_assertions) {
+        return Err("Fault injection is only available in development builds".to_string());
+    }
+    for p in [config.delay_probability, config.error_probability, config.drop_probability, config.malformed_probability] {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(format!("Fault probability must be between 0 and 1, got {}", p));
+        }
+    }
+
+    if config.is_enabled() {
+        println!("WARNING: bridge fault injection enabled: {:?}", config);
+    } else {
+        println!("Bridge fault injection disabled");
+    }
+    let lock = FAULT_INJECTION.get_or_init(|| Mutex::new(FaultInjectionConfig::default()));
+    *lock.lock().map_err(|e| e.to_string())? = config.clone();
+    Ok(config)
+}
+
+// ============ Bridge Binding ============
+// The listener binds before the frontend pushes any settings, so the interface comes from
+// the environment. Every request already needs the bridge token, but non-loopback binds are
+// still logged loudly, and all interfaces additionally need an explicit acknowledgement.
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeBinding {
+    /// Interface asked for via HL_TRADER_BRIDGE_BIND
+    requested: String,
+    /// Socket address actually bound, None if the bridge failed to start
+    address: Option<String>,
+    loopback: bool,
+    warning: Option<String>,
+    /// Why the last start failed; bridge-error is emitted before the frontend is listening
+    error: Option<String>,
+}
+
+static BRIDGE_BINDING: std::sync::OnceLock<Mutex<BridgeBinding>> = std::sync::OnceLock::new();
+
+fn bridge_binding() -> &'static Mutex<BridgeBinding> {
+    BRIDGE_BINDING.get_or_init(|| {
+        Mutex::new(BridgeBinding {
+            requested: "127.0.0.1".to_string(),
+            address: None,
+            loopback: true,
+            warning: None,
+            error: None,
+        })
+    })
+}
+
+/// Interface to bind plus a warning when the requested one was refused
+fn resolve_bind_address(requested: Option<&str>, risk_acknowledged: bool) -> (std::net::IpAddr, Option<String>) {
+    let loopback = std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+    let requested = match requested.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(requested) => requested,
+        None => return (loopback, None),
+    };
+    let addr = match requested.parse::<std::net::IpAddr>() {
+        Ok(addr) => addr,
+        Err(_) => return (loopback, Some(format!("Invalid bind address '{}', using 127.0.0.1", requested))),
+    };
+    if addr.is_loopback() {
+        return (addr, None);
+    }
+    if addr.is_unspecified() && !risk_acknowledged {
+        return (loopback, Some(format!(
+            "Refusing to bind {} (all interfaces) without {}=1, using 127.0.0.1",
+            addr, BRIDGE_RISK_ACK_ENV
+        )));
+    }
+    (addr, Some(format!(
+        "Bridge is reachable from other machines on {}; requests are only accepted with the bridge token",
+        addr
+    )))
+}
+
+/// Where the bridge is actually listening
+#[tauri::command]
+fn get_bridge_binding() -> BridgeBinding {
+    bridge_binding().lock().map(|b| b.clone()).unwrap_or_else(|e| e.into_inner().clone())
+}
+
+// ============ Health ============
+// Set by the frontend once its tradingview-* listeners are registered
+static FRONTEND_READY: AtomicBool = AtomicBool::new(false);
+
+#[tauri::command]
+fn set_frontend_ready(window: tauri::Window, ready: bool) -> Result<(), String> {
+    authorize(&window, "set_frontend_ready", CommandTier::Trading)?;
+    FRONTEND_READY.store(ready, Ordering::SeqCst);
+    Ok(())
+}
+
+// ============ CORS Origins ============
+// The bundled extension's content script fetches with the TradingView page origin. Unpacked
+// extensions get a per-install chrome-extension:// id, so those are added from the UI.
+const DEFAULT_ALLOWED_ORIGINS: [&str; 2] = ["https://www.tradingview.com", "https://tradingview.com"];
+const ORIGIN_SCHEMES: [&str; 4] = ["https://", "http://", "chrome-extension://", "moz-extension://"];
+
+/// Canonical form of an origin (lowercase, no trailing slash), None if it isn't one
+fn normalize_origin(origin: &str) -> Option<String> {
+    let origin = origin.trim().trim_end_matches('/').to_lowercase();
+    let host = ORIGIN_SCHEMES.iter().find_map(|scheme| origin.strip_prefix(scheme))?;
+    if host.is_empty() || host.contains(['/', '*', ' ', '?', '#']) {
+        return None;
+    }
+    Some(origin)
+}
+
+/// Access-Control-Allow-Origin header for a request: the matching allowed origin, None when the
+/// request has no Origin (not a browser CORS request, so no header is sent), Err when the origin
+/// is not allowed
+fn cors_allow_origin(request: &tiny_http::Request, allowed: &[String]) -> Result<Option<tiny_http::Header>, String> {
+    let origin = match request_header(request, "Origin") {
+        Some(origin) => origin,
+        None => return Ok(None),
+    };
+    match normalize_origin(origin) {
+        Some(normalized) if allowed.contains(&normalized) => {
+            Ok(Some(tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], normalized.as_bytes()).unwrap()))
+        }
+        _ => Err(origin.to_string()),
+    }
+}
+
+// Adds the Access-Control-Allow-Origin header when the request gets one
+trait WithAllowOrigin {
+    fn with_allow_origin(self, allow_origin: &Option<tiny_http::Header>) -> Self;
+}
+
+impl<R: std::io::Read> WithAllowOrigin for tiny_http::Response<R> {
+    fn with_allow_origin(self, allow_origin: &Option<tiny_http::Header>) -> Self {
+        match allow_origin {
+            Some(header) => self.with_header(header.clone()),
+            None => self,
+        }
+    }
+}
+
+/// Replace the list of origins allowed to call the bridge
+#[tauri::command]
+fn set_bridge_allowed_origins(window: tauri::Window, state: tauri::State<Arc<Mutex<BridgeSettings>>>, origins: Vec<String>) -> Result<Vec<String>, String> {
+    authorize(&window, "set_bridge_allowed_origins", CommandTier::Admin)?;
+    let mut normalized = Vec::new();
+    for origin in &origins {
+        match normalize_origin(origin) {
+            Some(origin) if !normalized.contains(&origin) => normalized.push(origin),
+            Some(_) => {}
+            None => return Err(format!("'{}' is not a valid origin (expected e.g. https://www.tradingview.com)", origin)),
+        }
+    }
+    let mut settings = state.lock().unwrap();
+    if settings.allowed_origins != normalized {
+        settings.allowed_origins = normalized.clone();
+        settings.modified_at = unix_millis() / 1000;
+        next_state_seq();
+    }
+    println!("Bridge allowed origins: {:?}", normalized);
+    Ok(normalized)
+}
+
+// ============ Bridge Token ============
+// Every bridge request except CORS preflights, the browser status page and the schemas must
+// carry "Authorization: Bearer <token>". The token is shown in the app as the extension
+// pairing code and persisted so pairing survives restarts.
+static BRIDGE_TOKEN: std::sync::OnceLock<Mutex<String>> = std::sync::OnceLock::new();
+
+fn generate_bridge_token() -> String {
+    rand::random::<[u8; 32]>().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn save_bridge_token(token: &str) {
+    let path = get_app_data_dir().join(BRIDGE_TOKEN_FILE);
+    if let Err(e) = std::fs::write(&path, token) {
+        eprintln!("Failed to persist bridge token: {}", e);
+        return;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+}
+
+fn bridge_token() -> &'static Mutex<String> {
+    BRIDGE_TOKEN.get_or_init(|| {
+        let persisted = std::fs::read_to_string(get_app_data_dir().join(BRIDGE_TOKEN_FILE))
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| token.len() >= 32);
+        let token = persisted.unwrap_or_else(|| {
+            let token = generate_bridge_token();
+            save_bridge_token(&token);
+            token
+        });
+        Mutex::new(token)
+    })
+}
+
+/// Compare without bailing out at the first differing byte
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn is_authorized(request: &tiny_http::Request) -> bool {
+    let presented = match request_header(request, "Authorization").and_then(|v| v.trim().strip_prefix("Bearer ")) {
+        Some(token) => token.trim(),
+        None => return false,
+    };
+    let expected = bridge_token().lock().unwrap_or_else(|e| e.into_inner());
+    tokens_match(presented, &expected)
+}
+
+/// Requests that don't need the token: public docs and the status page at /. Every other
+/// path needs it, whatever the Accept header says.
+fn is_public_route(request: &tiny_http::Request, url: &str) -> bool {
+    request.method() == &tiny_http::Method::Get && (url.split('?').next() == Some("/") || url.starts_with("/v1/schema/"))
+}
+
+/// Pairing code for the extension
+#[tauri::command]
+fn get_bridge_token(window: tauri::Window) -> Result<String, String> {
+    authorize(&window, "get_bridge_token", CommandTier::Secrets)?;
+    Ok(bridge_token().lock().unwrap_or_else(|e| e.into_inner()).clone())
+}
+
+/// Replace the token; the old one stops working immediately
+#[tauri::command]
+fn rotate_bridge_token(window: tauri::Window) -> Result<String, String> {
+    authorize(&window, "rotate_bridge_token", CommandTier::Secrets)?;
+    let token = generate_bridge_token();
+    save_bridge_token(&token);
+    *bridge_token().lock().unwrap_or_else(|e| e.into_inner()) = token.clone();
+    println!("Bridge token rotated");
+    Ok(token)
+}
+
+// ============ Bridge Port ============
+// The running listener, kept so it can be stopped when the port changes
+static BRIDGE_SERVER: std::sync::OnceLock<Mutex<Option<Arc<tiny_http::Server>>>> = std::sync::OnceLock::new();
+
+fn bridge_server() -> &'static Mutex<Option<Arc<tiny_http::Server>>> {
+    BRIDGE_SERVER.get_or_init(|| Mutex::new(None))
+}
+
+static ACTIVE_BRIDGE_WORKERS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+// Releases a worker slot when the handler finishes, panics included
+struct BridgeWorkerSlot;
+
+impl Drop for BridgeWorkerSlot {
+    fn drop(&mut self) {
+        ACTIVE_BRIDGE_WORKERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Port the bridge should listen on: HL_TRADER_BRIDGE_PORT if set, else the bridge_port setting
+fn configured_bridge_port(settings: &BridgeSettings) -> u16 {
+    std::env::var(BRIDGE_PORT_ENV)
+        .ok()
+        .and_then(|port| port.trim().parse::<u16>().ok())
+        .filter(|port| *port != 0)
+        .unwrap_or(settings.bridge_port)
+}
+
+/// Stop the current listener and start a new one on the configured port. A trade the old
+/// listener is still waiting on completes there; new requests go to the new port.
+fn restart_bridge_server(app_handle: tauri::AppHandle, settings: Arc<Mutex<BridgeSettings>>) {
+    if let Some(server) = bridge_server().lock().unwrap_or_else(|e| e.into_inner()).take() {
+        server.unblock();
+    }
+    start_bridge_server(app_handle, settings);
+}
+
+/// Port the bridge is actually listening on, None if it isn't running
+#[tauri::command]
+fn get_bridge_port() -> Option<u16> {
+    let server = bridge_server().lock().unwrap_or_else(|e| e.into_inner());
+    server.as_ref().and_then(|server| server.server_addr().to_ip()).map(|addr| addr.port())
+}
+
+/// Start the TradingView bridge HTTP server
+fn start_bridge_server(app_handle: tauri::AppHandle, settings: Arc<Mutex<BridgeSettings>>) {
+    thread::spawn(move || {
+        // Load or create the token before accepting anything
+        let _ = bridge_token();
+
+        let requested = std::env::var(BRIDGE_BIND_ENV).ok();
+        let risk_acknowledged = std::env::var(BRIDGE_RISK_ACK_ENV).is_ok_and(|v| v == "1");
+        let (bind_ip, warning) = resolve_bind_address(requested.as_deref(), risk_acknowledged);
+        if let Some(warning) = &warning {
+            eprintln!("Bridge binding: {}", warning);
+        }
+        if let Ok(mut binding) = bridge_binding().lock() {
+            *binding = BridgeBinding {
+                requested: requested.unwrap_or_else(|| "127.0.0.1".to_string()),
+                address: None,
+                loopback: bind_ip.is_loopback(),
+                warning,
+                error: None,
+            };
+        }
+
+        // Bind the configured port, or the first free one of the next few
+        let preferred = configured_bridge_port(&settings.lock().unwrap());
+        let mut bind_error = None;
+        let mut bound_server = None;
+        for port in preferred..=preferred.saturating_add(BRIDGE_FALLBACK_PORTS) {
+            match tiny_http::Server::http(std::net::SocketAddr::new(bind_ip, port)) {
+                Ok(s) => {
+                    bound_server = Some((Arc::new(s), port));
+                    break;
+                }
+                Err(e) => {
+                    eprintln!("Bridge could not bind port {}: {}", port, e);
+                    bind_error = Some(e.to_string());
+                }
+            }
+        }
+        let (server, port) = match bound_server {
+            Some(bound) => bound,
+            None => {
+                let error = bind_error.unwrap_or_else(|| "No port available".to_string());
+                eprintln!("Failed to start bridge server: {}", error);
+                if let Ok(mut binding) = bridge_binding().lock() {
+                    binding.error = Some(error.clone());
+                }
+                let _ = emit_change(&app_handle, "bridge-error", serde_json::json!({
+                    "error": error,
+                    "ports": [preferred, preferred.saturating_add(BRIDGE_FALLBACK_PORTS)],
+                }));
+                return;
+            }
+        };
+
+        let bound = server.server_addr().to_ip();
+        if let Ok(mut binding) = bridge_binding().lock() {
+            binding.address = bound.map(|addr| addr.to_string());
+        }
+
+        *bridge_server().lock().unwrap_or_else(|e| e.into_inner()) = Some(server.clone());
+
+        let started_at = Instant::now();
+        println!("TradingView bridge listening on {}", bound.map(|addr| addr.to_string()).unwrap_or_else(|| port.to_string()));
+        let _ = emit_change(&app_handle, "bridge-started", serde_json::json!({
+            "port": port,
+            "address": bound.map(|addr| addr.to_string()),
+            "fallback": port != preferred,
+        }));
+
+        for request in server.incoming_requests() {
+            // Bounded so a flood of requests can't spawn threads without limit
+            if ACTIVE_BRIDGE_WORKERS.fetch_add(1, Ordering::SeqCst) >= MAX_BRIDGE_WORKERS {
+                ACTIVE_BRIDGE_WORKERS.fetch_sub(1, Ordering::SeqCst);
+                let request_id = request_id_for(&request);
+                eprintln!("[{}] Bridge busy, rejecting {} {}", request_id, request.method(), request.url());
+                let response = BridgeResponse::error("busy", "Bridge busy").into_http(503);
+                let _ = respond_traced(request, response, &request_id);
+                continue;
+            }
+
+            let app_handle = app_handle.clone();
+            let settings = settings.clone();
+            thread::spawn(move || {
+                let _slot = BridgeWorkerSlot;
+                handle_bridge_request(app_handle, settings, request, port, started_at);
+            });
+        }
+
+        println!("TradingView bridge on port {} stopped", port);
+    });
+}
+
+/// Handle one bridge request. Runs on its own worker thread so a trade waiting on the
+/// frontend doesn't hold up /settings polls and /position posts.
+fn handle_bridge_request<R: tauri::Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    settings: Arc<Mutex<BridgeSettings>>,
+    request: tiny_http::Request,
+    port: u16,
+    started_at: Instant,
+) {
+    let url = request.url().to_string();
+    let request_id = request_id_for(&request);
+    println!("[{}] {} {}", request_id, request.method(), url);
+
+    // CORS headers for browser extension
+    // Only allowlisted browser origins get in, preflights included
+    let allowed_origins = settings.lock().unwrap().allowed_origins.clone();
+    let allow_origin = match cors_allow_origin(&request, &allowed_origins) {
+        Ok(origin) => origin,
+        Err(origin) => {
+            println!("[{}] Rejected request from origin {}", request_id, origin);
+            let response = BridgeResponse::error("origin_not_allowed", "Origin not allowed").into_http(403);
+            let _ = respond_traced(request, response, &request_id);
+            return;
+        }
+    };
+
+    let cors_headers = vec![
+        tiny_http::Header::from_bytes(&b"Access-Control-Allow-Methods"[..], &b"GET, HEAD, POST, PUT, OPTIONS"[..]).unwrap(),
+        tiny_http::Header::from_bytes(&b"Access-Control-Allow-Headers"[..], &b"Authorization, Content-Type, Idempotency-Key, If-None-Match, If-Modified-Since, X-Request-Id"[..]).unwrap(),
+    ];
+
+    // Handle preflight OPTIONS request
+    if request.method() == &tiny_http::Method::Options {
+        let response = tiny_http::Response::empty(200).with_allow_origin(&allow_origin)
+            .with_allow_origin(&allow_origin)
+            .with_header(cors_headers[1].clone());
+        let _ = respond_traced(request, response, &request_id);
+        return;
+    }
+
+    if !is_public_route(&request, &url) && !is_authorized(&request) {
+        let error = "Missing or invalid bridge token - pair the extension using the code shown in the app";
+        let response = BridgeResponse::error("unauthorized", error)
+            .into_http(401)
+            .with_allow_origin(&allow_origin)
+            .with_header(tiny_http::Header::from_bytes(&b"WWW-Authenticate"[..], &b"Bearer"[..]).unwrap());
+        let _ = respond_traced(request, response, &request_id);
+        return;
+    }
+
+    // Injected faults (dev builds only, see set_fault_injection)
+    let faults = fault_injection();
+    if faults.is_enabled() && faults.applies_to(&url) {
+        if roll_fault(faults.delay_probability) {
+            thread::sleep(Duration::from_millis(faults.delay_ms));
+        }
+        if roll_fault(faults.drop_probability) {
+            println!("Fault injection: dropping connection for {}", url);
+            drop(request.into_writer());
+            return;
+        }
+        if roll_fault(faults.error_probability) {
+            println!("Fault injection: 500 for {}", url);
+            let response = BridgeResponse::error("injected_fault", "Injected fault")
+                .into_http(500)
+                .with_allow_origin(&allow_origin);
+            let _ = respond_traced(request, response, &request_id);
+            return;
+        }
+        if roll_fault(faults.malformed_probability) {
+            println!("Fault injection: malformed JSON for {}", url);
+            let response = tiny_http::Response::from_string("{\"success\":tr")
+                .with_allow_origin(&allow_origin)
+                .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
+            let _ = respond_traced(request, response, &request_id);
+            return;
+        }
+    }
+    // Development builds tell the extension whether faults are being injected; release builds
+    // can't inject any, so the header isn't sent there
+    #[cfg(debug_assertions)]
+    let fault_header = tiny_http::Header::from_bytes(&b"X-Fault-Injection"[..], if faults.is_enabled() { &b"active"[..] } else { &b"off"[..] }).unwrap();
+
+    // POST and PUT bodies are read up front, capped in size and time
+    let has_body = matches!(request.method(), tiny_http::Method::Post | tiny_http::Method::Put);
+    let (request, body) = if has_body {
+        match read_body(request, BODY_READ_DEADLINE, &request_id, allow_origin.clone()) {
+            BodyRead::Complete(request, body) => (request, body),
+            BodyRead::Rejected(request, e) => {
+                println!("[{}] Rejected request body: {:?}", request_id, e);
+                let response = e.into_response().with_allow_origin(&allow_origin);
+                let _ = respond_traced(request, response, &request_id);
+                return;
+            }
+            BodyRead::Abandoned => {
+                println!("[{}] Request body not received within {:?}, giving up", request_id, BODY_READ_DEADLINE);
+                return;
+            }
+        }
+    } else {
+        (request, String::new())
+    };
+
+    // GET/HEAD /settings - return current settings (supports conditional requests)
+    if url == "/settings" && (request.method() == &tiny_http::Method::Get || request.method() == &tiny_http::Method::Head) {
+        let current_settings = settings.lock().unwrap().clone();
+        let json = serde_json::to_string(&current_settings).unwrap_or_else(|_| r#"{"risk":1,"leverage":25}"#.to_string());
+        let etag = body_etag(&json);
+        let last_modified = httpdate::fmt_http_date(std::time::UNIX_EPOCH + Duration::from_secs(current_settings.modified_at));
+        let not_modified = is_not_modified(&request, &etag, current_settings.modified_at);
+
+        // tiny_http strips the body for HEAD and 304 but keeps Content-Length
+        let response = if not_modified {
+            tiny_http::Response::from_string("").with_status_code(304)
+        } else {
+            tiny_http::Response::from_string(json)
+                .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap())
+        };
+        let response = response
+            .with_allow_origin(&allow_origin)
+            .with_header(tiny_http::Header::from_bytes(&b"ETag"[..], etag.as_bytes()).unwrap())
+            .with_header(tiny_http::Header::from_bytes(&b"Last-Modified"[..], last_modified.as_bytes()).unwrap());
+        #[cfg(debug_assertions)]
+        let response = response.with_header(fault_header);
+        let _ = respond_traced(request, response, &request_id);
+        return;
+    }
+
+    // PUT /settings (POST is an alias) - the extension changing risk, leverage or asset
+    if url == "/settings" && (request.method() == &tiny_http::Method::Put || request.method() == &tiny_http::Method::Post) {
+        let patch = match serde_json::from_str::<SettingsPatch>(&body) {
+            Ok(patch) => patch,
+            Err(e) => {
+                let response = BridgeResponse::error("invalid_request", format!("Invalid settings: {}", e))
+                    .into_http(400)
+                    .with_allow_origin(&allow_origin);
+                let _ = respond_traced(request, response, &request_id);
+                return;
+            }
+        };
+
+        let mut current = settings.lock().unwrap();
+        let updated = match apply_settings_patch(&current, patch) {
+            Ok(updated) => updated,
+            Err(errors) => {
+                drop(current);
+                let response = BridgeResponse::error("validation_failed", "Invalid settings")
+                    .with_data(serde_json::json!({ "fields": errors }))
+                    .into_http(422)
+                    .with_allow_origin(&allow_origin);
+                let _ = respond_traced(request, response, &request_id);
+                return;
+            }
+        };
+        let changed = updated != *current;
+        if changed {
+            *current = BridgeSettings { modified_at: unix_millis() / 1000, ..updated };
+        }
+        let new_settings = current.clone();
+        drop(current);
+
+        if changed {
+            println!("[{}] Settings changed by the extension", request_id);
+            let _ = emit_change(&app_handle, "bridge-settings-changed", &new_settings);
+        }
+        let json = serde_json::to_string(&new_settings).unwrap_or_default();
+        let response = tiny_http::Response::from_string(json.clone())
+            .with_allow_origin(&allow_origin)
+            .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap())
+            .with_header(tiny_http::Header::from_bytes(&b"ETag"[..], body_etag(&json).as_bytes()).unwrap());
+        let _ = respond_traced(request, response, &request_id);
+        return;
+    }
+
+    // GET /last-error?since=<ms> - recent user-facing errors for extension toasts
+    if url.split('?').next() == Some("/last-error") && request.method() == &tiny_http::Method::Get {
+        let since = query_param(&url, "since").and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+        let json = serde_json::to_string(&recent_bridge_errors(since)).unwrap_or_else(|_| "[]".to_string());
+        let response = compressible_response(&request, json)
+            .with_allow_origin(&allow_origin)
+            .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
+        let _ = respond_traced(request, response, &request_id);
+        return;
+    }
+
+    // GET/HEAD /health - is the app up and able to execute trades (HEAD for cheap uptime probes)
+    if url == "/health" && (request.method() == &tiny_http::Method::Get || request.method() == &tiny_http::Method::Head) {
+        let asset = settings.lock().unwrap().asset.clone();
+        let circuit = circuit_breaker().lock().map(|b| b.status(Instant::now())).ok();
+        let body = BridgeResponse::ok(serde_json::json!({
+            "status": if SHUTTING_DOWN.load(Ordering::SeqCst) { "shutting_down" } else { "ok" },
+            "version": app_handle.package_info().version.to_string(),
+            "apiVersion": BRIDGE_API_VERSION,
+            "uptimeSecs": started_at.elapsed().as_secs(),
+            "frontendReady": FRONTEND_READY.load(Ordering::SeqCst),
+            "asset": asset,
+            "port": port,
+            "extension": extension_state(),
+            "circuitBreaker": circuit,
+            "faultInjection": faults.is_enabled(),
+        }));
+        let response = body.into_http(200).with_allow_origin(&allow_origin);
+        #[cfg(debug_assertions)]
+        let response = response.with_header(fault_header);
+        let _ = respond_traced(request, response, &request_id);
+        return;
+    }
+
+    // GET /capabilities - how the bridge treats trades, so callers can size their own timeouts.
+    // The countdown is spent out of tradeTimeoutSecs: /execute-trade always answers within
+    // tradeTimeoutSecs, leaving resultWaitSecs of it for the result after the countdown.
+    if url == "/capabilities" && request.method() == &tiny_http::Method::Get {
+        let (countdown_secs, timeout_secs, max_leverage) = {
+            let settings = settings.lock().unwrap();
+            (settings.countdown_secs, settings.trade_timeout_secs, settings.max_leverage)
+        };
+        let body = BridgeResponse::ok(serde_json::json!({
+            "apiVersion": BRIDGE_API_VERSION,
+            "countdownSecs": countdown_secs,
+            "tradeTimeoutSecs": timeout_secs,
+            "resultWaitSecs": timeout_secs.saturating_sub(countdown_secs),
+            "countdownInTimeout": true,
+            "timeoutBudget": "countdownSecs is part of tradeTimeoutSecs; /execute-trade answers within tradeTimeoutSecs in total",
+            "maxLeverage": max_leverage,
+            "asyncTrades": true,
+        }));
+        let response = body.into_http(200).with_allow_origin(&allow_origin);
+        let _ = respond_traced(request, response, &request_id);
+        return;
+    }
+
+    // GET /v1/schema/{name} - JSON Schema for a bridge payload
+    if let Some(name) = url.strip_prefix("/v1/schema/") {
+        if request.method() == &tiny_http::Method::Get {
+            let (status, body) = match bridge_schema(name) {
+                Some(schema) => (200, schema.to_string()),
+                None => (404, BridgeResponse::error("not_found", format!("Unknown schema '{}'", name))
+                    .with_data(serde_json::json!({ "available": SCHEMA_NAMES }))
+                    .to_json()),
+            };
+            let response = compressible_response(&request, body)
+                .with_status_code(status)
+                .with_allow_origin(&allow_origin)
+                .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
+            let _ = respond_traced(request, response, &request_id);
+            return;
+        }
+    }
+
+    if url == "/position" && request.method() == &tiny_http::Method::Post {
+        let mut rejection = None;
+        let mut stale = None;
+        println!("[{}] Received position data: {}", request_id, body);
+        let (naming_policy, max_age_secs) = {
+            let settings = settings.lock().unwrap();
+            (settings.mixed_naming_policy.clone(), settings.position_max_age_secs)
+        };
+        let parsed = serde_json::from_str::<PositionData>(&body);
+        if let Err(e) = check_field_naming(&body, &naming_policy, &request_id) {
+            rejection = Some(e);
+        } else if let Err(e) = &parsed {
+            println!("[{}] Failed to parse position data: {}", request_id, e);
+            rejection = Some(format!("Invalid position: {}", e));
+        } else if let Some(reason) = parsed
+            .as_ref()
+            .ok()
+            .and_then(|position| stale_position_reason(position.timestamp, unix_millis(), max_age_secs))
+        {
+            // Goes to the app's log (via the event) and the extension's error toasts
+            record_bridge_error("stale_position", &reason, None, Some(&request_id));
+            let _ = emit_change(&app_handle, "tradingview-position-rejected", serde_json::json!({
+                "reason": reason,
+                "requestId": request_id,
+            }));
+            stale = Some(reason);
+        } else if let Ok(mut position_data) = parsed {
+            let (default_asset, tolerance_pct) = {
+                let settings = settings.lock().unwrap();
+                (settings.asset.clone(), settings.position_change_tolerance_pct)
+            };
+            let asset = match position_data.asset.as_deref() {
+                Some(symbol) if !symbol.trim().is_empty() => resolve_asset(symbol),
+                _ => default_asset,
+            };
+            position_data.asset = Some(asset.clone());
+            println!("Parsed position: {:?}", position_data);
+            let change = match tracked_positions().lock() {
+                Ok(mut positions) => {
+                    let change = classify_position_update(positions.get(&asset), &position_data, tolerance_pct);
+                    positions.insert(asset, position_data.clone());
+                    change
+                }
+                Err(_) => classify_position_update(None, &position_data, tolerance_pct),
+            };
+            println!("Position change: {}", change.kind);
+            // Emit event to frontend
+            match emit_change(&app_handle, "tradingview-position", PositionEvent { position: position_data, change, request_id: request_id.clone() }) {
+                Ok(_) => println!("Event emitted successfully"),
+                Err(e) => println!("Failed to emit event: {}", e),
+            }
+        }
+
+        let response = match (rejection, stale) {
+            (Some(error), _) => BridgeResponse::error("invalid_request", error).into_http(400),
+            (None, Some(reason)) => BridgeResponse::error("stale_position", reason).into_http(422),
+            (None, None) => BridgeResponse::success().into_http(200),
+        };
+        let response = response.with_allow_origin(&allow_origin);
+        let _ = respond_traced(request, response, &request_id);
+    } else if (url == "/hello" || url == "/heartbeat") && request.method() == &tiny_http::Method::Post {
+        let hello = serde_json::from_str::<HelloRequest>(&body).unwrap_or_default();
+        let resync = touch_extension(&app_handle, hello.version);
+        let heartbeat_secs = (settings.lock().unwrap().extension_heartbeat_timeout_secs / 3).max(1);
+        // resyncPositions: the app has no (fresh) chart positions, POST /position for every open tool
+        let body = BridgeResponse::ok(serde_json::json!({
+            "apiVersion": BRIDGE_API_VERSION,
+            "heartbeatIntervalSecs": heartbeat_secs,
+            "resyncPositions": resync,
+        }));
+        let response = body.into_http(200).with_allow_origin(&allow_origin);
+        let _ = respond_traced(request, response, &request_id);
+    } else if url == "/positions" && request.method() == &tiny_http::Method::Get {
+        let positions = tracked_positions().lock().map(|positions| positions.clone()).unwrap_or_default();
+        let body = BridgeResponse::ok(serde_json::json!({
+            "positions": positions,
+            "stale": chart_data_stale(),
+            "extension": extension_state(),
+        }));
+        let response = compressible_response(&request, body.to_json())
+            .with_allow_origin(&allow_origin)
+            .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
+        let _ = respond_traced(request, response, &request_id);
+    } else if url == "/position-closed" && request.method() == &tiny_http::Method::Post {
+        // Legacy senders post no body, which closes every tracked position
+        let closed = serde_json::from_str::<PositionClosedRequest>(&body).unwrap_or_default();
+        let asset = closed.asset.as_deref().map(resolve_asset);
+        if let Ok(mut positions) = tracked_positions().lock() {
+            match &asset {
+                Some(asset) => {
+                    positions.remove(asset);
+                }
+                None => positions.clear(),
+            }
+        }
+
+        // Emit close event to frontend
+        let _ = emit_change(&app_handle, "tradingview-position-closed", PositionClosedRequest { asset, request_id: Some(request_id.clone()) });
+
+        let response = BridgeResponse::success().into_http(200).with_allow_origin(&allow_origin);
+        let _ = respond_traced(request, response, &request_id);
+    } else if url.split('?').next() == Some("/execute-trade") && request.method() == &tiny_http::Method::Post {
+        // Execute trade from extension - wait for actual result, or with ?mode=async answer
+        // right away and let the extension poll /trade-status
+        let async_mode = query_param(&url, "mode") == Some("async");
+        println!("[{}] Received trade request: {}", request_id, body);
+        let naming_policy = settings.lock().unwrap().mixed_naming_policy.clone();
+        let naming_check = check_field_naming(&body, &naming_policy, &request_id);
+        let parsed = serde_json::from_str::<TradeRequest>(&body);
+        if SHUTTING_DOWN.load(Ordering::SeqCst) {
+            record_bridge_error("shutting_down", "App shutting down", None, Some(&request_id));
+            let response = BridgeResponse::error("shutting_down", "App shutting down")
+                .into_http(503)
+                .with_allow_origin(&allow_origin);
+            let _ = respond_traced(request, response, &request_id);
+        } else if let Err(error) = naming_check {
+            record_bridge_error("invalid_request", &error, None, Some(&request_id));
+            let response = BridgeResponse::error("invalid_request", error)
+                .into_http(400)
+                .with_allow_origin(&allow_origin);
+            let _ = respond_traced(request, response, &request_id);
+        } else if let Err(e) = &parsed {
+            println!("[{}] Failed to parse trade request: {}", request_id, e);
+            let error = format!("Invalid trade request: {}", e);
+            record_bridge_error("invalid_request", &error, None, Some(&request_id));
+            let response = BridgeResponse::error("invalid_request", error)
+                .into_http(400)
+                .with_allow_origin(&allow_origin);
+            let _ = respond_traced(request, response, &request_id);
+        } else if faults.is_enabled() {
+            // Safety interlock: never execute real trades while faults are being injected
+            let response = if faults.force_trade_timeout && faults.applies_to(&url) {
+                println!("Fault injection: forcing trade timeout");
+                BridgeResponse::error("trade_timeout", "Trade execution timeout").into_http(408)
+            } else {
+                println!("Fault injection active - trade answered as dry run, not executed");
+                BridgeResponse::ok(serde_json::json!({ "dryRun": true })).into_http(200)
+            };
+            let response = response.with_allow_origin(&allow_origin);
+            #[cfg(debug_assertions)]
+            let response = response.with_header(fault_header);
+            let _ = respond_traced(request, response, &request_id);
+        } else if let Ok(trade_request) = parsed {
+            println!("Executing trade: {:?}", trade_request);
+
+            let max_leverage = settings.lock().unwrap().max_leverage;
+            let mut trade_request = match validate_trade_request(&trade_request, max_leverage) {
+                Ok(trade_request) => trade_request,
+                Err(errors) => {
+                    let summary = summarize_field_errors(&errors);
+                    record_bridge_error("invalid_request", &summary, None, Some(&request_id));
+                    let response = BridgeResponse::error("validation_failed", format!("Invalid trade: {}", summary))
+                        .with_data(serde_json::json!({ "fields": errors }))
+                        .into_http(422)
+                        .with_allow_origin(&allow_origin);
+                    let _ = respond_traced(request, response, &request_id);
+                    return;
+                }
+            };
+
+            let trade_id = trade_request.trade_id.take();
+            if let Some(id) = trade_id.as_deref().filter(|id| !is_valid_trade_id(id)) {
+                let error = format!("Invalid tradeId '{}': use up to 64 letters, digits, '-' or '_'", id);
+                record_bridge_error("invalid_request", &error, None, Some(&request_id));
+                let response = BridgeResponse::error("invalid_request", error)
+                    .into_http(400)
+                    .with_allow_origin(&allow_origin);
+                let _ = respond_traced(request, response, &request_id);
+                return;
+            }
+
+            let configured_strategies = settings.lock().unwrap().strategies.clone();
+            match resolve_strategy(trade_request.strategy.as_deref(), &configured_strategies) {
+                Ok(strategy) => trade_request.strategy = Some(strategy),
+                Err(error) => {
+                    record_bridge_error("invalid_request", &error, None, Some(&request_id));
+                    let response = BridgeResponse::error("invalid_strategy", error)
+                        .with_data(serde_json::json!({ "strategies": configured_strategies }))
+                        .into_http(400)
+                        .with_allow_origin(&allow_origin);
+                    let _ = respond_traced(request, response, &request_id);
+                    return;
+                }
+            }
+
+            let allowed = circuit_breaker().lock().map(|mut breaker| breaker.allow(Instant::now())).unwrap_or(Ok(None));
+            let probe = match allowed {
+                Ok(probe) => probe,
+                Err(retry_after) => {
+                    let error = "Trading paused after repeated exchange errors";
+                    record_bridge_error("circuit_open", error, None, Some(&request_id));
+                    let response = BridgeResponse::error("circuit_open", error)
+                        .with_data(serde_json::json!({ "retryAfterSecs": retry_after.as_secs() }))
+                        .into_http(503)
+                        .with_allow_origin(&allow_origin);
+                    let _ = respond_traced(request, response, &request_id);
+                    return;
+                }
+            };
+
+            let trade_id = trade_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            let (countdown_secs, timeout_secs, duplicate_window_secs, asset) = {
+                let settings = settings.lock().unwrap();
+                (settings.countdown_secs, settings.trade_timeout_secs, settings.duplicate_trade_window_secs, settings.asset.clone())
+            };
+
+            let idempotency_key = request_header(&request, "Idempotency-Key").map(str::trim).filter(|key| !key.is_empty());
+            let claimed_key = match claim_trade_key(idempotency_key, &trade_request, &asset, &trade_id, duplicate_window_secs) {
+                Ok(key) => key,
+                Err(original) => {
+                    println!("[{}] Duplicate of trade {}, not executing", request_id, original);
+                    record_circuit_outcome(&app_handle, CircuitOutcome::NoVerdict, probe);
+                    let response = BridgeResponse::error("duplicate_trade", format!("Duplicate of trade {}", original))
+                        .with_data(trade_status(&original))
+                        .into_http(409)
+                        .with_allow_origin(&allow_origin);
+                    let _ = respond_traced(request, response, &request_id);
+                    return;
+                }
+            };
+
+            // Register the trade before the countdown so it can be cancelled from then on
+            let (tx, rx) = channel::<TradeResult>();
+            let registered = pending_trades()
+                .lock()
+                .map(|mut guard| {
+                    if guard.contains_key(&trade_id) {
+                        return false;
+                    }
+                    guard.insert(trade_id.clone(), PendingTrade { request: trade_request.clone(), sender: tx, started_at: Instant::now(), request_id: request_id.clone(), executing: false });
+                    true
+                })
+                .unwrap_or(false);
+            if !registered {
+                release_trade_key(claimed_key.as_deref(), &trade_id);
+                record_circuit_outcome(&app_handle, CircuitOutcome::NoVerdict, probe);
+                let response = BridgeResponse::error("duplicate_trade_id", format!("Trade {} is already pending", trade_id))
+                    .into_http(409)
+                    .with_allow_origin(&allow_origin);
+                let _ = respond_traced(request, response, &request_id);
+                return;
+            }
+
+            if async_mode {
+                // Answer now; the result is kept for GET /trade-status/{tradeId}
+                let response = BridgeResponse::ok(serde_json::json!({ "tradeId": trade_id, "status": "pending" }))
+                    .into_http(202)
+                    .with_allow_origin(&allow_origin);
+                let _ = respond_traced(request, response, &request_id);
+                let (status, _) = run_pending_trade(&app_handle, &trade_id, trade_request, &request_id, countdown_secs, timeout_secs, rx, probe);
+                println!("[{}] Async trade {} finished ({})", request_id, trade_id, status);
+            } else {
+                let (status, body) = run_pending_trade(&app_handle, &trade_id, trade_request, &request_id, countdown_secs, timeout_secs, rx, probe);
+                let response = body.into_http(status).with_allow_origin(&allow_origin);
+                let _ = respond_traced(request, response, &request_id);
+            }
+        }
+    } else if let Some(trade_id) = url.strip_prefix("/trade-status/").filter(|_| request.method() == &tiny_http::Method::Get) {
+        // Outcome of a trade sent with ?mode=async: pending, success, failed or interrupted
+        let status = trade_status(trade_id);
+        let response = if status["status"] == "unknown" {
+            BridgeResponse::error("unknown_trade", format!("No recent trade {}", trade_id)).into_http(404)
+        } else {
+            BridgeResponse::ok(status).into_http(200)
+        };
+        let _ = respond_traced(request, response.with_allow_origin(&allow_origin), &request_id);
+    } else if url == "/cancel-trade" && request.method() == &tiny_http::Method::Post {
+        let response = match serde_json::from_str::<CancelTradeRequest>(&body) {
+            Err(e) => BridgeResponse::error("invalid_request", format!("Invalid cancel request: {}", e)).into_http(400),
+            Ok(cancel) => {
+                let trade_id = cancel.trade_id;
+                match cancel_pending_trade(&trade_id) {
+                    Ok(()) => {
+                        println!("[{}] Trade {} cancelled by the extension", request_id, trade_id);
+                        // Stops the countdown, if one is running
+                        abort_active_countdown(&trade_id);
+                        let _ = emit_change(&app_handle, "tradingview-cancel-trade", serde_json::json!({
+                            "tradeId": trade_id,
+                            "requestId": request_id,
+                        }));
+                        BridgeResponse::ok(serde_json::json!({ "tradeId": trade_id })).into_http(200)
+                    }
+                    Err(CancelRefused::Executing) => BridgeResponse::error("already_executing", "Trade is already executing")
+                        .with_data(serde_json::json!({ "tradeId": trade_id }))
+                        .into_http(409),
+                    Err(CancelRefused::Completed(result)) => BridgeResponse::error("already_completed", "Trade already completed")
+                        .with_data(serde_json::json!({ "tradeId": trade_id, "result": result }))
+                        .into_http(409),
+                    Err(CancelRefused::Unknown) => BridgeResponse::error("unknown_trade", format!("No pending trade {}", trade_id)).into_http(404),
+                }
+            }
+        };
+        let _ = respond_traced(request, response.with_allow_origin(&allow_origin), &request_id);
+    } else if request.method() == &tiny_http::Method::Get && wants_html(&request) {
+        // Someone opened the bridge in a browser to see whether the app is running
+        let found = url.split('?').next() == Some("/");
+        let response = tiny_http::Response::from_string(render_status_page(found))
+            .with_status_code(if found { 200 } else { 404 })
+            .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..]).unwrap());
+        let _ = respond_traced(request, response, &request_id);
+    } else if let Some(methods) = allowed_methods(&url) {
+        let error = format!("Method {} not allowed, use {}", request.method(), methods);
+        let response = BridgeResponse::error("method_not_allowed", error)
+            .into_http(405)
+            .with_allow_origin(&allow_origin)
+            .with_header(tiny_http::Header::from_bytes(&b"Allow"[..], methods.as_bytes()).unwrap());
+        let _ = respond_traced(request, response, &request_id);
+    } else {
+        let response = BridgeResponse::error("not_found", "Not Found")
+            .into_http(404)
+            .with_allow_origin(&allow_origin);
+        let _ = respond_traced(request, response, &request_id);
+    }
+}
+
+/// Take a registered trade through the countdown and the frontend, returning the status and body
+/// for /execute-trade. Every path leaves the trade resolved, so /trade-status has its outcome, and
+/// records its circuit outcome under `probe`, the token it got from the breaker.
+#[allow(clippy::too_many_arguments)]
+fn run_pending_trade<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    trade_id: &str,
+    trade_request: TradeRequest,
+    request_id: &str,
+    countdown_secs: u64,
+    timeout_secs: u64,
+    rx: std::sync::mpsc::Receiver<TradeResult>,
+    probe: Option<u64>,
+) -> (u16, BridgeResponse) {
+    // Optional grace countdown, taken out of the same timeout budget
+    if countdown_secs > 0 && !run_trade_countdown(app_handle, trade_id, &trade_request, countdown_secs) {
+        record_circuit_outcome(app_handle, CircuitOutcome::NoVerdict, probe);
+        let aborted = TradeResult {
+            success: false,
+            error: Some("Trade aborted during countdown".to_string()),
+            resolution: TradeResolution::Reported,
+        };
+        let _ = resolve_pending_trade(trade_id, aborted);
+        // Cancelled via /cancel-trade, or aborted from the app
+        let body = match rx.try_recv() {
+            Ok(result) if result.resolution == TradeResolution::Cancelled => BridgeResponse::error("cancelled", "cancelled"),
+            _ => BridgeResponse::error("trade_aborted", "Trade aborted during countdown"),
+        };
+        return (200, body.with_data(serde_json::json!({ "tradeId": trade_id })));
+    }
+    let wait = Duration::from_secs(timeout_secs.saturating_sub(countdown_secs));
+
+    // Emit event to frontend to execute the trade
+    let event = TradeExecuteEvent { trade: trade_request, trade_id: trade_id.to_string(), request_id: request_id.to_string() };
+    // The pending lock is held through the emit, so a cancel lands either before (no
+    // event is sent and the wait below picks it up) or after, when it's refused as executing
+    let emitted = match pending_trades().lock() {
+        Ok(mut guard) => match guard.get_mut(trade_id) {
+            Some(trade) => {
+                trade.executing = true;
+                emit_change(app_handle, "tradingview-execute-trade", event)
+            }
+            None => Ok(()),
+        },
+        Err(_) => emit_change(app_handle, "tradingview-execute-trade", event),
+    };
+    if let Err(e) = emitted {
+        println!("Failed to emit trade event: {}", e);
+        record_circuit_outcome(app_handle, CircuitOutcome::NoVerdict, probe);
+        record_bridge_error("emit_failed", &e.to_string(), Some(trade_id), Some(request_id));
+        let failed = TradeResult { success: false, error: Some(e.to_string()), resolution: TradeResolution::Reported };
+        let _ = resolve_pending_trade(trade_id, failed);
+        return (500, BridgeResponse::error("emit_failed", e.to_string()));
+    }
+    println!("[{}] Trade {} emitted, waiting for result...", request_id, trade_id);
+    bridge_log(request_id, Some(trade_id), "Trade emitted to the frontend");
+
+    // Wait for result up to the configured timeout (Drift on-chain txs can be slow).
+    // On timeout, whichever of the timeout, a late result or a cancel resolved the
+    // trade first is what ends up in the channel.
+    let received = rx.recv_timeout(wait).or_else(|_| {
+        let timed_out = TradeResult {
+            success: false,
+            error: Some(format!("Trade execution timeout: no result after {}s", timeout_secs)),
+            resolution: TradeResolution::TimedOut,
+        };
+        let _ = resolve_pending_trade(trade_id, timed_out);
+        rx.try_recv()
+    });
+    match received {
+        Ok(result) if result.resolution == TradeResolution::Cancelled => {
+            println!("[{}] Trade {} cancelled", request_id, trade_id);
+            bridge_log(request_id, Some(trade_id), "Trade cancelled");
+            record_circuit_outcome(app_handle, CircuitOutcome::NoVerdict, probe);
+            (200, BridgeResponse::error("cancelled", "cancelled").with_data(serde_json::json!({ "tradeId": trade_id })))
+        }
+        Ok(result) if result.resolution == TradeResolution::Interrupted => {
+            // Says nothing about the exchange
+            println!("[{}] Trade {} interrupted by shutdown", request_id, trade_id);
+            bridge_log(request_id, Some(trade_id), "Trade interrupted by shutdown");
+            record_circuit_outcome(app_handle, CircuitOutcome::NoVerdict, probe);
+            (503, BridgeResponse::error("shutting_down", "App shutting down").with_data(serde_json::json!({ "tradeId": trade_id })))
+        }
+        Ok(result) if result.resolution == TradeResolution::Reported => {
+            println!("[{}] Trade result received: {:?}", request_id, result);
+            bridge_log(request_id, Some(trade_id), format!("Trade result received (success: {})", result.success));
+            let body = if result.success {
+                record_circuit_outcome(app_handle, CircuitOutcome::Success, probe);
+                BridgeResponse::success()
+            } else {
+                let error = result.error.unwrap_or_else(|| "Trade failed".to_string());
+                let outcome = if is_user_caused_error(&error) { CircuitOutcome::NoVerdict } else { CircuitOutcome::Failure };
+                record_circuit_outcome(app_handle, outcome, probe);
+                record_bridge_error("trade_failed", &error, Some(trade_id), Some(request_id));
+                BridgeResponse::error("trade_failed", error)
+            };
+            (200, body.with_data(serde_json::json!({ "tradeId": trade_id })))
+        }
+        _ => {
+            println!("[{}] Trade result timeout after {}s", request_id, timeout_secs);
+            bridge_log(request_id, Some(trade_id), format!("Trade timed out after {}s", timeout_secs));
+            let error = format!("Trade execution timeout: no result after {}s", timeout_secs);
+            record_circuit_outcome(app_handle, CircuitOutcome::Failure, probe);
+            record_bridge_error("trade_timeout", &error, Some(trade_id), Some(request_id));
+            let body = BridgeResponse::error("trade_timeout", error)
+                .with_data(serde_json::json!({ "tradeId": trade_id, "timeoutSecs": timeout_secs }));
+            (408, body)
+        }
+    }
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    install_panic_hook();
+
+    // Create shared settings state
+    let bridge_settings = Arc::new(Mutex::new(BridgeSettings::default()));
+    let bridge_settings_clone = bridge_settings.clone();
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(bridge_settings)
+        .setup(move |app| {
+            if get_app_data_dir().join(PENDING_TRADE_MARKER).exists() {
+                println!("Previous session quit with an unresolved trade - check the exchange");
+            }
+            if get_app_data_dir().join(CRASH_REPORT_FILE).exists() {
+                println!("Crash report from a previous session is available");
+            }
+            println!("Platform capabilities: {:?}", platform_capabilities());
+
+            // Start the TradingView bridge server with shared settings
+            start_extension_monitor(app.handle().clone(), bridge_settings_clone.clone());
+            start_bridge_server(app.handle().clone(), bridge_settings_clone.clone());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            keychain_save,
+            keychain_load,
+            keychain_delete,
+            keychain_has_password,
+            update_bridge_settings,
+            report_trade_result,
+            check_biometric_available,
+            authenticate_biometric,
+            http_get,
+            http_post,
+            take_pending_trade_marker,
+            set_fault_injection,
+            abort_countdown,
+            get_last_crash_report,
+            dismiss_crash_report,
+            get_platform_capabilities,
+            quick_trade,
+            get_bridge_binding,
+            get_circuit_breaker,
+            get_bridge_log,
+            get_permission_audit,
+            reset_circuit_breaker,
+            get_app_snapshot,
+            get_bridge_port,
+            get_bridge_token,
+            rotate_bridge_token,
+            set_bridge_allowed_origins,
+            set_frontend_ready
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| match event {
+            tauri::RunEvent::ExitRequested { api, .. } => handle_exit_requested(app_handle, &api),
+            tauri::RunEvent::Exit => finish_shutdown(),
+            _ => {}
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    // ---- JSON Schema checks ----
+
+    /// Minimal JSON Schema check (type, enum, required, properties, items, minimum, $ref and
+    /// *Of) - enough for what schemars generates for the bridge payloads
+    fn validate(value: &Value, schema: &Value, root: &Value) -> Result<(), String> {
+        if let Some(allowed) = schema.as_bool() {
+            return if allowed { Ok(()) } else { Err("rejected by false schema".to_string()) };
+        }
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.trim_start_matches("#/definitions/");
+            let target = root
+                .get("definitions")
+                .and_then(|defs| defs.get(name))
+                .ok_or_else(|| format!("unresolved {}", reference))?;
+            return validate(value, target, root);
+        }
+        if let Some(types) = schema.get("type") {
+            let allowed: Vec<&str> = match types {
+                Value::String(ty) => vec![ty.as_str()],
+                Value::Array(tys) => tys.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !allowed.iter().any(|ty| type_matches(value, ty)) {
+                return Err(format!("{} is not {:?}", value, allowed));
+            }
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                return Err(format!("{} is not one of {:?}", value, options));
+            }
+        }
+        if let (Some(min), Some(n)) = (schema.get("minimum").and_then(Value::as_f64), value.as_f64()) {
+            if n < min {
+                return Err(format!("{} is below the minimum {}", n, min));
+            }
+        }
+        for sub in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+            validate(value, sub, root)?;
+        }
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any.iter().any(|sub| validate(value, sub, root).is_ok()) {
+                return Err(format!("{} matches none of anyOf", value));
+            }
+        }
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            if one.iter().filter(|sub| validate(value, sub, root).is_ok()).count() != 1 {
+                return Err(format!("{} does not match exactly one of oneOf", value));
+            }
+        }
+        if let Some(obj) = value.as_object() {
+            for field in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+                let field = field.as_str().unwrap_or_default();
+                if !obj.contains_key(field) {
+                    return Err(format!("missing required field '{}'", field));
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, field_value) in obj {
+                match properties.and_then(|props| props.get(key)) {
+                    Some(field_schema) => validate(field_value, field_schema, root).map_err(|e| format!("{}: {}", key, e))?,
+                    None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                        return Err(format!("unexpected field '{}'", key));
+                    }
+                    None => {}
+                }
+            }
+        }
+        if let (Some(items), Some(arr)) = (schema.get("items"), value.as_array()) {
+            for item in arr {
+                validate(item, items, root)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn type_matches(value: &Value, ty: &str) -> bool {
+        match ty {
+            "null" => value.is_null(),
+            "boolean" => value.is_boolean(),
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            _ => false,
+        }
+    }
+
+    fn check_schema(name: &str, value: &Value) -> Result<(), String> {
+        let schema = bridge_schema(name).unwrap_or_else(|| panic!("no schema named '{}'", name));
+        validate(value, &schema, &schema)
+    }
+
+    fn assert_matches_schema(name: &str, value: &Value) {
+        if let Err(e) = check_schema(name, value) {
+            panic!("{} does not match schema '{}': {}", value, name, e);
+        }
+    }
+
+    fn sample_trade() -> TradeRequest {
+        serde_json::from_value(json!({
+            "direction": "long",
+            "entry": 100.0,
+            "stopLoss": 95.0,
+            "takeProfit": 110.0,
+            "risk": 1.0,
+            "leverage": 5,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn every_published_schema_is_versioned() {
+        for name in SCHEMA_NAMES {
+            let schema = bridge_schema(name).unwrap_or_else(|| panic!("no schema named '{}'", name));
+            assert_eq!(schema["x-bridge-api-version"], BRIDGE_API_VERSION, "{}", name);
+            assert!(schema["$id"].as_str().unwrap().ends_with(name), "{}", name);
+        }
+        assert!(bridge_schema("unknown").is_none());
+    }
+
+    #[test]
+    fn bridge_payloads_round_trip_through_their_schemas() {
+        // Payloads as the extension sends them, deserialized and serialized back
+        let trade: TradeRequest = serde_json::from_value(json!({
+            "direction": "short",
+            "entry": 100.0,
+            "stop_loss": 104.5,
+            "takeProfit": null,
+            "risk": 2.5,
+            "leverage": 10,
+            "chartContext": { "interval": "15", "symbol": "BYBIT:BTCUSDT.P", "chartId": "abc" },
+            "strategy": "breakout",
+        }))
+        .unwrap();
+        assert_matches_schema("trade-request", &serde_json::to_value(&trade).unwrap());
+        assert_matches_schema("trade-request", &serde_json::to_value(sample_trade()).unwrap());
+
+        let position: PositionData = serde_json::from_value(json!({
+            "direction": "long",
+            "entry": 50000.0,
+            "stopLoss": 49000.0,
+            "takeProfit": 52000.0,
+            "timestamp": 1_700_000_000_000u64,
+            "asset": "BTC",
+        }))
+        .unwrap();
+        assert_matches_schema("position-data", &serde_json::to_value(&position).unwrap());
+
+        assert_matches_schema("bridge-settings", &serde_json::to_value(BridgeSettings::default()).unwrap());
+
+        let result = TradeResult { success: false, error: Some("Rejected".to_string()), resolution: TradeResolution::Reported };
+        assert_matches_schema("trade-result", &serde_json::to_value(&result).unwrap());
+
+        let parse = |body: String| serde_json::from_str::<Value>(&body).unwrap();
+        assert_matches_schema("response-success", &parse(BridgeResponse::success().to_json()));
+        assert_matches_schema("response-success", &parse(BridgeResponse::ok(json!({ "tradeId": "t-1" })).to_json()));
+        assert_matches_schema("response-error", &parse(BridgeResponse::error("validation_failed", "bad entry").to_json()));
+        assert_matches_schema(
+            "response-error",
+            &parse(BridgeResponse::error("trade_timeout", "timed out").with_data(json!({ "tradeId": "t-1" })).to_json()),
+        );
+    }
+
+    #[test]
+    fn schema_check_catches_drifted_payloads() {
+        let mut trade = serde_json::to_value(sample_trade()).unwrap();
+        trade.as_object_mut().unwrap().remove("stopLoss");
+        assert!(check_schema("trade-request", &trade).is_err());
+
+        let mut trade = serde_json::to_value(sample_trade()).unwrap();
+        trade["entry"] = json!("100");
+        assert!(check_schema("trade-request", &trade).is_err());
+
+        trade["entry"] = json!(100.0);
+        trade["leverage"] = json!(-1);
+        assert!(check_schema("trade-request", &trade).is_err());
+
+        let success = serde_json::from_str::<Value>(&BridgeResponse::success().to_json()).unwrap();
+        assert!(check_schema("response-error", &success).is_err());
+    }
+
+    // ---- Position updates ----
+
+    fn position(direction: &str, entry: f64, stop_loss: f64, take_profit: Option<f64>) -> PositionData {
+        PositionData {
+            direction: direction.to_string(),
+            entry,
+            stop_loss,
+            take_profit,
+            timestamp: 0,
+            asset: None,
+            chart_context: None,
+        }
+    }
+
+    #[test]
+    fn classify_position_update_kinds() {
+        let base = position("long", 100.0, 95.0, Some(110.0));
+        let cases = [
+            ("no previous position", None, position("long", 100.0, 95.0, Some(110.0)), "new"),
+            ("direction flipped", Some(&base), position("short", 100.0, 105.0, Some(90.0)), "new"),
+            ("direction case ignored", Some(&base), position("LONG", 100.0, 95.0, Some(110.0)), "unchanged"),
+            ("identical", Some(&base), position("long", 100.0, 95.0, Some(110.0)), "unchanged"),
+            ("within tolerance", Some(&base), position("long", 100.04, 95.04, Some(110.04)), "unchanged"),
+            ("entry moved", Some(&base), position("long", 101.0, 95.0, Some(110.0)), "moved-entry"),
+            ("whole tool dragged", Some(&base), position("long", 101.0, 96.0, Some(111.0)), "moved-entry"),
+            ("stop moved", Some(&base), position("long", 100.0, 94.0, Some(110.0)), "moved-stop"),
+            ("stop and tp moved", Some(&base), position("long", 100.0, 94.0, Some(112.0)), "moved-stop"),
+            ("tp moved", Some(&base), position("long", 100.0, 95.0, Some(112.0)), "moved-tp"),
+            ("tp removed", Some(&base), position("long", 100.0, 95.0, None), "moved-tp"),
+        ];
+        for (name, previous, next, expected) in cases {
+            assert_eq!(classify_position_update(previous, &next, 0.05).kind, expected, "{}", name);
+        }
+
+        let no_tp = position("long", 100.0, 95.0, None);
+        assert_eq!(classify_position_update(Some(&no_tp), &base, 0.05).kind, "moved-tp", "tp added");
+        assert_eq!(classify_position_update(Some(&no_tp), &no_tp, 0.05).kind, "unchanged", "no tp either side");
+        // Zero tolerance reports any change at all
+        let nudged = position("long", 100.0, 95.0, Some(110.01));
+        assert_eq!(classify_position_update(Some(&base), &nudged, 0.0).kind, "moved-tp", "zero tolerance");
+    }
+
+    #[test]
+    fn classify_position_update_deltas() {
+        let base = position("short", 100.0, 105.0, Some(90.0));
+        let change = classify_position_update(Some(&base), &position("short", 98.0, 104.0, Some(85.0)), 0.05);
+        assert_eq!(change.deltas.entry, -2.0);
+        assert_eq!(change.deltas.stop_loss, -1.0);
+        assert_eq!(change.deltas.take_profit, Some(-5.0));
+
+        let change = classify_position_update(Some(&base), &position("short", 100.0, 105.0, None), 0.05);
+        assert_eq!(change.deltas.take_profit, None);
+
+        let change = classify_position_update(None, &base, 0.05);
+        assert_eq!((change.deltas.entry, change.deltas.stop_loss, change.deltas.take_profit), (0.0, 0.0, None));
+    }
+
+    // ---- Content negotiation ----
+
+    fn request_with_header(name: &str, value: &str) -> tiny_http::Request {
+        tiny_http::TestRequest::new()
+            .with_header(tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap())
+            .into()
+    }
+
+    #[test]
+    fn wants_html_follows_accept_header() {
+        let cases = [
+            ("text/html", true),
+            ("TEXT/HTML", true),
+            ("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8", true),
+            ("application/json, text/html;q=0.5", true),
+            ("text/html;q=0", false),
+            ("text/html; q=0.0, application/json", false),
+            ("*/*", false),
+            ("application/json", false),
+            ("text/plain", false),
+            ("", false),
+        ];
+        for (accept, expected) in cases {
+            assert_eq!(wants_html(&request_with_header("Accept", accept)), expected, "Accept: {}", accept);
+        }
+        assert!(!wants_html(&tiny_http::TestRequest::new().into()), "no Accept header");
+    }
+
+    // ---- Command permissions ----
+
+    type MockWindow = tauri::Window<tauri::test::MockRuntime>;
+
+    fn mock_window(app: &tauri::App<tauri::test::MockRuntime>, label: &str) -> MockWindow {
+        tauri::WebviewWindowBuilder::new(app, label, tauri::WebviewUrl::default())
+            .build()
+            .unwrap()
+            .as_ref()
+            .window()
+    }
+
+    #[test]
+    fn authorize_checks_window_tiers() {
+        let app = tauri::test::mock_app();
+        let main = mock_window(&app, "main");
+        let unknown = mock_window(&app, "embedded");
+        let cases = [
+            (&main, CommandTier::ReadOnly, true),
+            (&main, CommandTier::Trading, true),
+            (&main, CommandTier::Secrets, true),
+            (&main, CommandTier::Admin, true),
+            (&unknown, CommandTier::ReadOnly, true),
+            (&unknown, CommandTier::Trading, false),
+            (&unknown, CommandTier::Secrets, false),
+            (&unknown, CommandTier::Admin, false),
+        ];
+        for (window, tier, allowed) in cases {
+            let result = authorize(window, "test_command", tier);
+            assert_eq!(result.is_ok(), allowed, "window '{}' {:?}", window.label(), tier);
+            if let Err(e) = result {
+                assert!(e.starts_with("permission_denied: test_command"), "{}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn commands_reject_windows_without_their_tier() {
+        let app = tauri::test::mock_app();
+        let main = mock_window(&app, "main");
+        let unknown = mock_window(&app, "embedded");
+
+        assert_eq!(abort_countdown(main.clone(), "perm-unknown-trade".to_string()), Ok(false));
+        let denied = abort_countdown(unknown.clone(), "perm-unknown-trade".to_string()).unwrap_err();
+        assert!(denied.starts_with("permission_denied"), "{}", denied);
+        let denied = take_pending_trade_marker(unknown.clone()).unwrap_err();
+        assert!(denied.starts_with("permission_denied"), "{}", denied);
+        let denied = keychain_has_password(unknown.clone()).unwrap_err();
+        assert!(denied.starts_with("permission_denied"), "{}", denied);
+        let fetched = tauri::async_runtime::block_on(http_get(unknown.clone(), "http://127.0.0.1:9/".to_string()));
+        assert_eq!(fetched.status, 403);
+        assert!(fetched.error.unwrap_or_default().starts_with("permission_denied"));
+
+        // A denied abort leaves the countdown running; an allowed one stops it
+        let trade_id = "perm-running-trade".to_string();
+        active_countdowns().lock().unwrap().insert(trade_id.clone(), false);
+        assert!(abort_countdown(unknown, trade_id.clone()).is_err());
+        assert_eq!(active_countdowns().lock().unwrap().get(&trade_id), Some(&false));
+        assert_eq!(abort_countdown(main, trade_id.clone()), Ok(true));
+        assert_eq!(active_countdowns().lock().unwrap().remove(&trade_id), Some(true));
+    }
+
+    #[test]
+    fn permission_denials_are_audited() {
+        let app = tauri::test::mock_app();
+        let main = mock_window(&app, "main");
+        let iframe = mock_window(&app, "audit-test-iframe");
+
+        assert!(authorize(&iframe, "audit_test_command", CommandTier::Secrets).is_err());
+        assert!(authorize(&main, "audit_test_allowed", CommandTier::Secrets).is_ok());
+
+        let audit = get_permission_audit(main).unwrap();
+        let entry = audit.iter().find(|entry| entry.command == "audit_test_command").expect("audit entry");
+        assert_eq!(entry.window, "audit-test-iframe");
+        assert_eq!(entry.tier, CommandTier::Secrets);
+        assert!(audit.iter().all(|entry| entry.command != "audit_test_allowed"));
+
+        // Reading the audit log is itself an admin command
+        assert!(get_permission_audit(iframe).unwrap_err().starts_with("permission_denied"));
+    }
+
+    // ---- Biometric prompt reason ----
+
+    #[test]
+    fn sanitize_biometric_reason_neutralises_hostile_input() {
+        let cases = [
+            ("Unlock vault", "Unlock vault"),
+            ("  padded\t reason  ", "padded reason"),
+            ("line one\nline two\r\nthree", "line one line two three"),
+            ("bell\u{7}and\u{1b}[31mescape", "bell and [31mescape"),
+            ("it's \"quoted\" `backticked`", "it's \"quoted\" `backticked`"),
+            ("'; rm -rf ~; echo '", "'; rm -rf ~; echo '"),
+            ("$(reboot) && $HOME | tee > /tmp/x", "$(reboot) && $HOME | tee > /tmp/x"),
+            ("null\0byte", "null byte"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(sanitize_biometric_reason(input).as_deref(), Ok(expected), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn sanitize_biometric_reason_caps_and_rejects() {
+        let long = "a".repeat(10_000);
+        assert_eq!(sanitize_biometric_reason(&long).unwrap().chars().count(), MAX_BIOMETRIC_REASON_CHARS);
+        // Counted in characters, so multi-byte input is never split mid-character
+        let wide = "é".repeat(500);
+        assert_eq!(sanitize_biometric_reason(&wide).unwrap(), "é".repeat(MAX_BIOMETRIC_REASON_CHARS));
+
+        for empty in ["", "   ", "\n\t\r", "\u{0}\u{1}"] {
+            let err = sanitize_biometric_reason(empty).unwrap_err();
+            assert!(err.starts_with("invalid_reason"), "{:?}: {}", empty, err);
+        }
+    }
+
+    // ---- Payload field naming ----
+
+    #[test]
+    fn detect_field_naming_classifies_payload_keys() {
+        let cases = [
+            (r#"{"direction":"long","stopLoss":1,"takeProfit":2}"#, FieldNaming::CamelCase),
+            (r#"{"direction":"long","stop_loss":1,"take_profit":2}"#, FieldNaming::SnakeCase),
+            (r#"{"stopLoss":1,"take_profit":2}"#, FieldNaming::Mixed),
+            (r#"{"some_Key":1}"#, FieldNaming::Mixed),
+            (r#"{"direction":"long","entry":1}"#, FieldNaming::Neutral),
+            // Only top-level keys count, not nested objects or values
+            (r#"{"entry":1,"chart":{"chart_id":"x"},"note":"stopLoss"}"#, FieldNaming::Neutral),
+            ("{}", FieldNaming::Neutral),
+            ("[]", FieldNaming::Neutral),
+            ("not json", FieldNaming::Neutral),
+            ("", FieldNaming::Neutral),
+        ];
+        for (body, expected) in cases {
+            assert_eq!(detect_field_naming(body), expected, "{}", body);
+        }
+    }
+
+    #[test]
+    fn check_field_naming_applies_mixed_policy() {
+        let mixed = r#"{"stopLoss":1,"take_profit":2}"#;
+        let camel = r#"{"stopLoss":1,"takeProfit":2}"#;
+        let cases = [
+            (mixed, "allow", true),
+            (mixed, "warn", true),
+            (mixed, "reject", false),
+            (camel, "reject", true),
+            (mixed, "unknown-policy", true),
+        ];
+        for (body, policy, accepted) in cases {
+            assert_eq!(check_field_naming(body, policy, "test").is_ok(), accepted, "{} with {}", body, policy);
+        }
+    }
+
+    // ---- Circuit breaker ----
+
+    fn test_breaker() -> CircuitBreaker {
+        CircuitBreaker::new(3, Duration::from_secs(60), Duration::from_secs(120))
+    }
+
+    #[test]
+    fn circuit_breaker_opens_half_opens_and_closes() {
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        let mut breaker = test_breaker();
+
+        // Closed: failures below the threshold still let trades through
+        for secs in [0, 10] {
+            assert_eq!(breaker.allow(at(secs)), Ok(None));
+            breaker.record(CircuitOutcome::Failure, None, at(secs));
+        }
+        assert_eq!(breaker.state, CircuitState::Closed);
+        assert_eq!(breaker.status(at(10)).consecutive_failures, 2);
+
+        // Third failure inside the window opens it
+        breaker.record(CircuitOutcome::Failure, None, at(20));
+        assert_eq!(breaker.state, CircuitState::Open);
+        assert_eq!(breaker.allow(at(50)), Err(Duration::from_secs(90)));
+        assert_eq!(breaker.status(at(50)).retry_after_secs, Some(90));
+
+        // After the cool-down one probe goes through, others wait for its verdict
+        let probe = breaker.allow(at(140)).unwrap();
+        assert!(probe.is_some());
+        assert_eq!(breaker.state, CircuitState::HalfOpen);
+        assert_eq!(breaker.allow(at(141)), Err(Duration::ZERO));
+
+        // A successful probe closes it and forgets the failures
+        breaker.record(CircuitOutcome::Success, probe, at(142));
+        assert_eq!(breaker.state, CircuitState::Closed);
+        assert_eq!(breaker.status(at(142)).consecutive_failures, 0);
+        assert_eq!(breaker.status(at(142)).retry_after_secs, None);
+        assert_eq!(breaker.allow(at(143)), Ok(None));
+    }
+
+    #[test]
+    fn circuit_breaker_failed_probe_reopens() {
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        let mut breaker = test_breaker();
+        for secs in [0, 1, 2] {
+            breaker.record(CircuitOutcome::Failure, None, at(secs));
+        }
+        let probe = breaker.allow(at(122)).unwrap();
+
+        // No verdict (trade aborted before the exchange) frees the probe without closing
+        breaker.record(CircuitOutcome::NoVerdict, probe, at(123));
+        assert_eq!(breaker.state, CircuitState::HalfOpen);
+        let probe = breaker.allow(at(124)).unwrap();
+        assert!(probe.is_some());
+
+        // A single failed probe re-opens and restarts the cool-down
+        breaker.record(CircuitOutcome::Failure, probe, at(125));
+        assert_eq!(breaker.state, CircuitState::Open);
+        assert_eq!(breaker.allow(at(126)), Err(Duration::from_secs(119)));
+
+        breaker.reset();
+        assert_eq!(breaker.state, CircuitState::Closed);
+        assert_eq!(breaker.allow(at(127)), Ok(None));
+    }
+
+    #[test]
+    fn circuit_breaker_ignores_late_outcomes_of_other_trades() {
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        let mut breaker = test_breaker();
+
+        // Let through while closed, still running when the circuit opens
+        assert_eq!(breaker.allow(at(0)), Ok(None));
+        for secs in [1, 2, 3] {
+            breaker.record(CircuitOutcome::Failure, None, at(secs));
+        }
+        assert_eq!(breaker.state, CircuitState::Open);
+
+        // Its late success neither closes the open circuit...
+        breaker.record(CircuitOutcome::Success, None, at(4));
+        assert_eq!(breaker.state, CircuitState::Open);
+
+        // ...nor decides the half-open state or frees the probe slot
+        let probe = breaker.allow(at(124)).unwrap();
+        for outcome in [CircuitOutcome::Success, CircuitOutcome::Failure, CircuitOutcome::NoVerdict] {
+            breaker.record(outcome, None, at(125));
+            assert_eq!(breaker.state, CircuitState::HalfOpen, "{:?}", outcome);
+            assert_eq!(breaker.allow(at(125)), Err(Duration::ZERO), "{:?}", outcome);
+        }
+
+        // A stale token from an earlier probe doesn't count either
+        breaker.record(CircuitOutcome::Success, probe.map(|token| token + 1), at(126));
+        assert_eq!(breaker.state, CircuitState::HalfOpen);
+
+        breaker.record(CircuitOutcome::Success, probe, at(127));
+        assert_eq!(breaker.state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn circuit_breaker_forgets_failures_outside_the_window() {
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        let mut breaker = test_breaker();
+        for secs in [0, 50, 100, 150] {
+            breaker.record(CircuitOutcome::Failure, None, at(secs));
+        }
+        assert_eq!(breaker.state, CircuitState::Closed);
+        assert_eq!(breaker.status(at(150)).consecutive_failures, 2);
+    }
+
+    #[test]
+    fn user_caused_errors_are_recognised() {
+        let cases = [
+            ("Cancelled by user", true),
+            ("Insufficient margin to place order", true),
+            ("Trade rejected by user", true),
+            ("Permission DENIED", true),
+            ("Exchange unavailable (502)", false),
+            ("Invalid agent key", false),
+            ("", false),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(is_user_caused_error(error), expected, "{}", error);
+        }
+    }
+
+    // ---- App snapshot ----
+
+    #[test]
+    fn snapshot_seq_covers_every_earlier_change_event() {
+        use tauri::Listener;
+
+        const ASSET: &str = "SNAPSHOT-TEST";
+        const CHANGES: u64 = 300;
+        let app = tauri::test::mock_app();
+        app.manage(Arc::new(Mutex::new(BridgeSettings::default())));
+
+        let events = Arc::new(Mutex::new(Vec::<(u64, f64)>::new()));
+        let recorded = events.clone();
+        app.listen_any("snapshot-test-change", move |event| {
+            let payload: Value = serde_json::from_str(event.payload()).unwrap();
+            recorded.lock().unwrap().push((payload["seq"].as_u64().unwrap(), payload["entry"].as_f64().unwrap()));
+        });
+
+        // Same order as the real handlers: apply the change, then emit it
+        let handle = app.handle().clone();
+        let writer = thread::spawn(move || {
+            for i in 1..=CHANGES {
+                let entry = i as f64;
+                tracked_positions().lock().unwrap().insert(ASSET.to_string(), position("long", entry, entry - 1.0, None));
+                emit_change(&handle, "snapshot-test-change", json!({ "entry": entry })).unwrap();
+            }
+        });
+
+        let mut snapshots = Vec::new();
+        while !writer.is_finished() {
+            let snapshot = get_app_snapshot(app.state());
+            let entry = snapshot.positions.get(ASSET).map(|p| p.entry).unwrap_or(0.0);
+            snapshots.push((snapshot.seq, entry));
+        }
+        writer.join().unwrap();
+        tracked_positions().lock().unwrap().remove(ASSET);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len() as u64, CHANGES);
+        assert!(events.windows(2).all(|pair| pair[0].0 < pair[1].0), "event seqs must increase");
+        assert!(snapshots.windows(2).all(|pair| pair[0].0 <= pair[1].0), "snapshot seqs must not go backwards");
+        // An event the frontend would drop (seq <= snapshot seq) must already be in the snapshot
+        for (snapshot_seq, snapshot_entry) in &snapshots {
+            for (event_seq, event_entry) in events.iter().filter(|(seq, _)| seq <= snapshot_seq) {
+                assert!(
+                    event_entry <= snapshot_entry,
+                    "snapshot seq {} shows entry {} but event seq {} (entry {}) is missing",
+                    snapshot_seq, snapshot_entry, event_seq, event_entry
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn emit_change_adds_seq_to_object_payloads() {
+        use tauri::Listener;
+
+        let app = tauri::test::mock_app();
+        let payloads = Arc::new(Mutex::new(Vec::<Value>::new()));
+        let recorded = payloads.clone();
+        app.listen_any("seq-test-change", move |event| {
+            recorded.lock().unwrap().push(serde_json::from_str(event.payload()).unwrap());
+        });
+
+        emit_change(app.handle(), "seq-test-change", json!({ "tradeId": "t-1" })).unwrap();
+        emit_change(app.handle(), "seq-test-change", json!({ "maxWaitSecs": 30 })).unwrap();
+
+        let payloads = payloads.lock().unwrap();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0]["tradeId"], "t-1");
+        assert!(payloads[1]["seq"].as_u64().unwrap() > payloads[0]["seq"].as_u64().unwrap());
+    }
+
+    // ---- Bridge handler ----
+    // Each exchange binds a throwaway listener, writes raw HTTP at it from a client thread and
+    // runs the request through handle_bridge_request against a mock app.
+
+    type MockHandle = tauri::AppHandle<tauri::test::MockRuntime>;
+
+    /// The token the handler checks, fixed before anything could load or generate the real one
+    fn test_bridge_token() -> String {
+        BRIDGE_TOKEN
+            .get_or_init(|| Mutex::new("test-bridge-token-0123456789abcdef".to_string()))
+            .lock()
+            .unwrap()
+            .clone()
+    }
+
+    fn test_settings() -> Arc<Mutex<BridgeSettings>> {
+        Arc::new(Mutex::new(BridgeSettings::default()))
+    }
+
+    struct BridgeReply {
+        status: u16,
+        head: String,
+        body: String,
+    }
+
+    impl BridgeReply {
+        fn json(&self) -> Value {
+            serde_json::from_str(&self.body).unwrap_or_else(|e| panic!("{} in {:?}", e, self.body))
+        }
+
+        fn header(&self, name: &str) -> Option<&str> {
+            self.head.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.eq_ignore_ascii_case(name).then(|| value.trim())
+            })
+        }
+    }
+
+    /// Request carrying the bridge token; `Connection: close` so the reply can be read to EOF
+    fn bridge_request(method: &str, path: &str, headers: &[(&str, &str)], body: &str) -> Vec<u8> {
+        let mut raw = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n",
+            method,
+            path,
+            test_bridge_token(),
+            body.len()
+        );
+        for (name, value) in headers {
+            raw.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        raw.push_str("\r\n");
+        raw.push_str(body);
+        raw.into_bytes()
+    }
+
+    fn exchange(app: &MockHandle, settings: &Arc<Mutex<BridgeSettings>>, raw: Vec<u8>) -> BridgeReply {
+        use std::io::Write;
+
+        exchange_with(app, settings, move |stream| stream.write_all(&raw).unwrap())
+    }
+
+    /// Like exchange, with the client's side of the connection written by `send`
+    fn exchange_with(
+        app: &MockHandle,
+        settings: &Arc<Mutex<BridgeSettings>>,
+        send: impl FnOnce(&mut std::net::TcpStream) + Send + 'static,
+    ) -> BridgeReply {
+        use std::io::Read;
+
+        let _ = test_bridge_token();
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            send(&mut stream);
+            let mut reply = String::new();
+            let _ = stream.read_to_string(&mut reply);
+            reply
+        });
+        let request = server.recv().unwrap();
+        handle_bridge_request(app.clone(), settings.clone(), request, addr.port(), Instant::now());
+
+        let reply = client.join().unwrap();
+        let (head, body) = reply.split_once("\r\n\r\n").unwrap_or((&reply, ""));
+        BridgeReply {
+            status: head.split(' ').nth(1).and_then(|code| code.parse().ok()).unwrap_or(0),
+            head: head.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn html_accept_does_not_bypass_the_token() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        let from_browser = |path: &str| {
+            format!("GET {} HTTP/1.1\r\nHost: localhost\r\nAccept: text/html\r\nConnection: close\r\n\r\n", path).into_bytes()
+        };
+
+        for path in ["/settings", "/positions", "/health", "/last-error", "/trade-status/abc"] {
+            let reply = exchange(app.handle(), &settings, from_browser(path));
+            assert_eq!(reply.status, 401, "{}", path);
+            assert_eq!(reply.json()["code"], "unauthorized", "{}", path);
+        }
+
+        let page = exchange(app.handle(), &settings, from_browser("/"));
+        assert_eq!(page.status, 200);
+        assert!(page.header("Content-Type").is_some_and(|ty| ty.starts_with("text/html")));
+        let schema = exchange(app.handle(), &settings, from_browser("/v1/schema/trade-request"));
+        assert_eq!(schema.status, 200);
+
+        let paired = exchange(app.handle(), &settings, bridge_request("GET", "/settings", &[("Accept", "text/html")], ""));
+        assert_eq!(paired.status, 200);
+        assert_eq!(paired.json()["leverage"], json!(settings.lock().unwrap().leverage));
+    }
+
+    // ---- Response compression ----
+
+    #[test]
+    fn accepts_gzip_reads_accept_encoding() {
+        let cases = [
+            ("gzip", true),
+            ("GZIP", true),
+            ("deflate, gzip;q=0.5", true),
+            ("*", true),
+            ("br, deflate", false),
+            ("gzip;q=0", false),
+            ("gzip; q=0.0", false),
+            ("identity", false),
+            ("", false),
+        ];
+        for (accept, expected) in cases {
+            assert_eq!(accepts_gzip(&request_with_header("Accept-Encoding", accept)), expected, "{:?}", accept);
+        }
+        assert!(!accepts_gzip(&tiny_http::TestRequest::new().into()));
+    }
+
+    #[test]
+    fn compressible_response_gzips_large_bodies_for_gzip_clients() {
+        use std::io::Read;
+
+        let large = "x".repeat(COMPRESSION_MIN_BYTES);
+        let small = "x".repeat(COMPRESSION_MIN_BYTES - 1);
+        let cases = [
+            ("gzip", &large, true),
+            ("gzip", &small, false),
+            ("gzip;q=0", &large, false),
+            ("identity", &large, false),
+        ];
+        for (accept, body, gzipped) in cases {
+            let response = compressible_response(&request_with_header("Accept-Encoding", accept), body.clone());
+            let header = |name: &'static str| {
+                response
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv(name))
+                    .map(|h| h.value.as_str().to_string())
+            };
+            assert_eq!(header("Vary").as_deref(), Some("Accept-Encoding"), "{:?} {}", accept, body.len());
+            assert_eq!(header("Content-Encoding").is_some(), gzipped, "{:?} {}", accept, body.len());
+
+            let mut decoded = String::new();
+            if gzipped {
+                flate2::read::GzDecoder::new(response.into_reader()).read_to_string(&mut decoded).unwrap();
+            } else {
+                response.into_reader().read_to_string(&mut decoded).unwrap();
+            }
+            assert_eq!(&decoded, body);
+        }
+    }
+
+    #[test]
+    fn health_answers_head_without_a_body() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+
+        let get = exchange(app.handle(), &settings, bridge_request("GET", "/health", &[], ""));
+        assert_eq!(get.status, 200);
+        assert_eq!(get.json()["success"], true);
+
+        let head = exchange(app.handle(), &settings, bridge_request("HEAD", "/health", &[], ""));
+        assert_eq!(head.status, 200);
+        assert!(head.body.is_empty());
+
+        let delete = exchange(app.handle(), &settings, bridge_request("DELETE", "/health", &[], ""));
+        assert_eq!(delete.status, 405);
+        assert_eq!(delete.header("Allow"), Some("GET, HEAD"));
+    }
+
+    fn trade_body(trade_id: &str, entry: f64) -> String {
+        json!({
+            "direction": "long",
+            "entry": entry,
+            "stopLoss": entry * 0.95,
+            "risk": 1.0,
+            "leverage": 5,
+            "tradeId": trade_id,
+        })
+        .to_string()
+    }
+
+    /// POST /execute-trade on its own thread, since it blocks until the trade is resolved
+    fn spawn_trade(app: &MockHandle, settings: &Arc<Mutex<BridgeSettings>>, body: String) -> thread::JoinHandle<BridgeReply> {
+        let (app, settings) = (app.clone(), settings.clone());
+        thread::spawn(move || exchange(&app, &settings, bridge_request("POST", "/execute-trade", &[("Content-Type", "application/json")], &body)))
+    }
+
+    /// Raw payloads of an event, as the frontend receives them
+    fn capture_events(app: &tauri::App<tauri::test::MockRuntime>, event: &str) -> std::sync::mpsc::Receiver<String> {
+        use tauri::Listener;
+
+        let (tx, rx) = channel();
+        app.listen_any(event, move |event| {
+            let _ = tx.send(event.payload().to_string());
+        });
+        rx
+    }
+
+    #[test]
+    fn concurrent_trades_get_their_own_results_in_any_order() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        let emitted = capture_events(&app, "tradingview-execute-trade");
+
+        let first = spawn_trade(app.handle(), &settings, trade_body("order-test-first", 506.1));
+        let first_event = emitted.recv_timeout(Duration::from_secs(5)).unwrap();
+        let second = spawn_trade(app.handle(), &settings, trade_body("order-test-second", 506.2));
+        let second_event = emitted.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // The id travels next to the trade, once
+        for (payload, trade_id) in [(&first_event, "order-test-first"), (&second_event, "order-test-second")] {
+            assert_eq!(payload.matches("\"tradeId\"").count(), 1, "{}", payload);
+            assert_eq!(serde_json::from_str::<Value>(payload).unwrap()["tradeId"], trade_id);
+        }
+
+        let reported = |success: bool, error: Option<&str>| TradeResult {
+            success,
+            error: error.map(str::to_string),
+            resolution: TradeResolution::Reported,
+        };
+        resolve_pending_trade("order-test-second", reported(true, None)).unwrap();
+        let second = second.join().unwrap();
+        assert_eq!(second.status, 200);
+        assert_eq!(second.json()["success"], true);
+        assert_eq!(second.json()["tradeId"], "order-test-second");
+
+        resolve_pending_trade("order-test-first", reported(false, Some("Insufficient margin"))).unwrap();
+        let first = first.join().unwrap();
+        assert_eq!(first.status, 200);
+        assert_eq!(first.json()["success"], false);
+        assert_eq!(first.json()["error"], "Insufficient margin");
+        assert_eq!(first.json()["tradeId"], "order-test-first");
+    }
+
+    #[test]
+    fn trade_id_is_serialized_only_when_set() {
+        let mut trade = sample_trade();
+        assert!(serde_json::to_value(&trade).unwrap().get("tradeId").is_none());
+        trade.trade_id = Some("marker-trade".to_string());
+        let value = serde_json::to_value(&trade).unwrap();
+        assert_eq!(value["tradeId"], "marker-trade");
+        assert_matches_schema("trade-request", &value);
+    }
+
+    /// Mock app with the notification plugin, which the trade countdown uses
+    fn mock_app_with_notifications() -> tauri::App<tauri::test::MockRuntime> {
+        tauri::test::mock_builder()
+            .plugin(tauri_plugin_notification::init())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap()
+    }
+
+    #[test]
+    fn countdown_is_part_of_the_trade_timeout() {
+        let app = mock_app_with_notifications();
+        let settings = test_settings();
+        {
+            let mut settings = settings.lock().unwrap();
+            settings.countdown_secs = 1;
+            settings.trade_timeout_secs = 2;
+        }
+
+        // No result ever arrives: the caller hears back after the timeout, not countdown + timeout
+        let started = Instant::now();
+        let reply = spawn_trade(app.handle(), &settings, trade_body("timeout-test-budget", 508.0)).join().unwrap();
+        let elapsed = started.elapsed();
+        assert_json_error(&reply, 408, "trade_timeout");
+        assert_eq!(reply.json()["timeoutSecs"], 2);
+        assert!(elapsed >= Duration::from_millis(1900) && elapsed < Duration::from_millis(2900), "answered after {:?}", elapsed);
+    }
+
+    #[test]
+    fn countdown_leaves_room_for_the_result() {
+        let cases = [(0, 60, 0), (10, 60, 10), (30, 30, 25), (30, 10, 5), (5, 5, 0), (30, 300, 30)];
+        for (countdown, timeout, expected) in cases {
+            assert_eq!(countdown_within_budget(countdown, timeout), expected, "{}s countdown, {}s timeout", countdown, timeout);
+        }
+    }
+
+    fn assert_json_error(reply: &BridgeReply, status: u16, code: &str) {
+        assert_eq!(reply.status, status, "{}", reply.body);
+        assert_eq!(reply.header("Content-Type"), Some("application/json"));
+        let body = reply.json();
+        assert_eq!(body["success"], false, "{}", reply.body);
+        assert_eq!(body["code"], code, "{}", reply.body);
+        assert!(body["error"].as_str().is_some_and(|error| !error.is_empty()), "{}", reply.body);
+    }
+
+    #[test]
+    fn bad_bodies_get_json_errors() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        let json_type = [("Content-Type", "application/json")];
+        let cases = [
+            ("/execute-trade", r#"{"direction": "long", "entry": 100"#),
+            ("/execute-trade", r#"{"direction": "long", "entry": "100", "stopLoss": 95, "takeProfit": null, "risk": 1, "leverage": 5}"#),
+            ("/execute-trade", "[]"),
+            ("/execute-trade", ""),
+            ("/position", "not json"),
+            ("/position", r#"{"direction": "long", "entry": 100, "stopLoss": true, "takeProfit": null, "timestamp": 0}"#),
+            ("/settings", r#"{"risk": "high"}"#),
+            ("/cancel-trade", r#"{"tradeId": 7}"#),
+        ];
+        for (path, body) in cases {
+            let reply = exchange(app.handle(), &settings, bridge_request("POST", path, &json_type, body));
+            assert_json_error(&reply, 400, "invalid_request");
+        }
+    }
+
+    #[test]
+    fn truncated_body_gets_a_json_error() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+
+        // Content-Length promises more than the client sends before closing its side. Bodies
+        // over 1KiB are streamed by tiny_http, so this one reaches the handler; what did arrive
+        // is valid JSON, which must not be mistaken for the whole body.
+        let body = format!(r#"{{"tradeId": "truncated-test"}}{}"#, " ".repeat(2048));
+        let mut raw = bridge_request("POST", "/cancel-trade", &[("Content-Type", "application/json")], &body);
+        raw.truncate(raw.len() - 1024);
+        let truncated = exchange_with(app.handle(), &settings, move |stream| {
+            use std::io::Write;
+
+            stream.write_all(&raw).unwrap();
+            stream.shutdown(std::net::Shutdown::Write).unwrap();
+        });
+        assert_json_error(&truncated, 400, "invalid_request");
+    }
+
+    #[test]
+    fn unknown_routes_and_methods_get_json_errors() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+
+        let missing = exchange(app.handle(), &settings, bridge_request("GET", "/no-such-route", &[], ""));
+        assert_json_error(&missing, 404, "not_found");
+
+        let wrong_method = exchange(app.handle(), &settings, bridge_request("GET", "/execute-trade", &[], ""));
+        assert_json_error(&wrong_method, 405, "method_not_allowed");
+        assert_eq!(wrong_method.header("Allow"), Some("POST"));
+
+        let wrong_schema_method = exchange(app.handle(), &settings, bridge_request("POST", "/v1/schema/trade-request", &[], ""));
+        assert_json_error(&wrong_schema_method, 405, "method_not_allowed");
+        assert_eq!(wrong_schema_method.header("Allow"), Some("GET"));
+    }
+
+    fn cancel_request(trade_id: &str) -> Vec<u8> {
+        bridge_request("POST", "/cancel-trade", &[("Content-Type", "application/json")], &json!({ "tradeId": trade_id }).to_string())
+    }
+
+    #[test]
+    fn cancel_is_refused_once_the_trade_was_emitted() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        let emitted = capture_events(&app, "tradingview-execute-trade");
+
+        let trade = spawn_trade(app.handle(), &settings, trade_body("race-test-emitted", 510.1));
+        emitted.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // Cancel and result race; whichever lands first, the result is what the trade ends with
+        let cancel = {
+            let (handle, settings) = (app.handle().clone(), settings.clone());
+            thread::spawn(move || exchange(&handle, &settings, cancel_request("race-test-emitted")))
+        };
+        resolve_pending_trade("race-test-emitted", TradeResult { success: true, error: None, resolution: TradeResolution::Reported }).unwrap();
+
+        let cancel = cancel.join().unwrap();
+        assert_eq!(cancel.status, 409, "{}", cancel.body);
+        let code = cancel.json()["code"].as_str().unwrap_or_default().to_string();
+        assert!(code == "already_executing" || code == "already_completed", "{}", code);
+
+        let trade = trade.join().unwrap();
+        assert_eq!(trade.json()["success"], true, "{}", trade.body);
+
+        // Once emitted, a cancel never wins even before the result arrives
+        let trade = spawn_trade(app.handle(), &settings, trade_body("race-test-pending", 510.2));
+        emitted.recv_timeout(Duration::from_secs(5)).unwrap();
+        let cancel = exchange(app.handle(), &settings, cancel_request("race-test-pending"));
+        assert_eq!(cancel.status, 409);
+        assert_eq!(cancel.json()["code"], "already_executing");
+        resolve_pending_trade("race-test-pending", TradeResult { success: false, error: Some("Order rejected".to_string()), resolution: TradeResolution::Reported }).unwrap();
+        assert_eq!(trade.join().unwrap().json()["error"], "Order rejected");
+    }
+
+    #[test]
+    fn cancel_during_the_countdown_stops_the_trade() {
+        let app = mock_app_with_notifications();
+        let settings = test_settings();
+        settings.lock().unwrap().countdown_secs = 5;
+        let countdown = capture_events(&app, "trade-countdown");
+        let emitted = capture_events(&app, "tradingview-execute-trade");
+
+        let trade = spawn_trade(app.handle(), &settings, trade_body("race-test-countdown", 510.3));
+        countdown.recv_timeout(Duration::from_secs(5)).unwrap();
+        let cancel = exchange(app.handle(), &settings, cancel_request("race-test-countdown"));
+        assert_eq!(cancel.status, 200, "{}", cancel.body);
+
+        let trade = trade.join().unwrap();
+        assert_eq!(trade.json()["code"], "cancelled");
+        assert!(emitted.try_recv().is_err(), "a cancelled trade must never reach the frontend");
+    }
+
+    // ---- Quick trade ----
+
+    fn quick_trade_settings(asset: &str) -> BridgeSettings {
+        BridgeSettings {
+            asset: asset.to_string(),
+            price: 100.0,
+            risk: 1.0,
+            leverage: 5,
+            ..BridgeSettings::default()
+        }
+    }
+
+    #[test]
+    fn quick_trade_accepts_valid_levels() {
+        let proposal = propose_quick_trade(&quick_trade_settings("QT-VALID"), "Long").unwrap();
+        assert_eq!(proposal.trade.direction, "long");
+        assert_eq!(proposal.source, "last_price");
+        assert!(proposal.trade.stop_loss < proposal.trade.entry);
+        assert!(proposal.requires_confirmation);
+    }
+
+    #[test]
+    fn quick_trade_rejects_what_a_bridge_trade_would() {
+        let chart = |asset: &str, position: PositionData| {
+            tracked_positions().lock().unwrap().insert(asset.to_string(), position);
+            quick_trade_settings(asset)
+        };
+        let cases: Vec<(&str, BridgeSettings, &str, &str)> = vec![
+            ("direction", quick_trade_settings("QT-DIRECTION"), "sideways", "Invalid direction"),
+            ("no price", BridgeSettings { price: 0.0, ..quick_trade_settings("QT-NO-PRICE") }, "long", "No chart position or last price"),
+            ("stop not positive", BridgeSettings { quick_trade_stop_pct: 150.0, ..quick_trade_settings("QT-STOP-PCT") }, "long", "stopLoss must be a positive number"),
+            ("stop above long entry", chart("QT-LONG-STOP", position("long", 100.0, 105.0, None)), "long", "stopLoss must be below entry for a long"),
+            ("stop below short entry", chart("QT-SHORT-STOP", position("short", 100.0, 95.0, None)), "short", "stopLoss must be above entry for a short"),
+            ("take profit below long entry", chart("QT-LONG-TP", position("long", 100.0, 95.0, Some(90.0))), "long", "takeProfit must be above entry for a long"),
+            ("take profit above short entry", chart("QT-SHORT-TP", position("short", 100.0, 105.0, Some(110.0))), "short", "takeProfit must be below entry for a short"),
+            ("risk zero", BridgeSettings { risk: 0.0, ..quick_trade_settings("QT-RISK-ZERO") }, "long", "risk must be greater than 0"),
+            ("risk too high", BridgeSettings { risk: 150.0, ..quick_trade_settings("QT-RISK-HIGH") }, "long", "risk must be greater than 0"),
+            ("leverage over max", BridgeSettings { leverage: 80, max_leverage: 50, ..quick_trade_settings("QT-LEVERAGE") }, "long", "leverage must be between 1 and 50"),
+            ("leverage zero", BridgeSettings { leverage: 0, ..quick_trade_settings("QT-LEVERAGE-ZERO") }, "long", "leverage must be between 1"),
+        ];
+        for (name, settings, direction, expected) in cases {
+            let error = propose_quick_trade(&settings, direction).map(|p| p.trade).unwrap_err();
+            assert!(error.contains(expected), "{}: {}", name, error);
+        }
+    }
+
+    // ---- Position freshness ----
+
+    #[test]
+    fn stale_position_reason_checks_age_and_skew() {
+        let now = 1_700_000_000_000;
+        let cases: [(u64, u64, Option<&str>); 9] = [
+            (now, 30, None),
+            (now - 29_999, 30, None),
+            // Exactly at the limit is still fresh
+            (now - 30_000, 30, None),
+            (now - 30_001, 30, Some("Position is 30s old (max 30s)")),
+            (now - 120_000, 30, Some("Position is 120s old (max 30s)")),
+            (0, 300, Some("Position is 1700000000s old (max 300s)")),
+            // Clocks a little ahead of ours are tolerated
+            (now + POSITION_FUTURE_SKEW_MS, 30, None),
+            (now + POSITION_FUTURE_SKEW_MS + 1, 30, Some("Position timestamp is 5001ms in the future")),
+            (now + 60_000, 30, Some("Position timestamp is 60000ms in the future")),
+        ];
+        for (timestamp, max_age_secs, expected) in cases {
+            assert_eq!(
+                stale_position_reason(timestamp, now, max_age_secs).as_deref(),
+                expected,
+                "timestamp {} max {}s",
+                timestamp,
+                max_age_secs
+            );
+        }
+    }
+
+    // ---- Request bodies ----
+
+    #[test]
+    fn oversized_bodies_get_413() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+
+        let declared = exchange(app.handle(), &settings, bridge_request("POST", "/position", &[], &"x".repeat(MAX_BODY_BYTES + 1)));
+        assert_json_error(&declared, 413, "payload_too_large");
+
+        // Chunked bodies have no Content-Length to check up front; the cap applies while reading
+        let body = "x".repeat(MAX_BODY_BYTES + 1);
+        let raw = format!(
+            "POST /position HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAuthorization: Bearer {}\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+            test_bridge_token(),
+            body.len(),
+            body
+        );
+        let streamed = exchange(app.handle(), &settings, raw.into_bytes());
+        assert_json_error(&streamed, 413, "payload_too_large");
+    }
+
+    #[test]
+    fn slow_trickle_bodies_are_abandoned_at_the_deadline() {
+        use std::io::{Read, Write};
+
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        // Over 1KiB, so tiny_http hands the request over before the body is in
+        let client = thread::spawn(move || {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(b"POST /position HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: 4096\r\n\r\n{")
+                .unwrap();
+            for _ in 0..20 {
+                thread::sleep(Duration::from_millis(50));
+                if stream.write_all(b" ").is_err() {
+                    break;
+                }
+            }
+            let _ = stream.shutdown(std::net::Shutdown::Write);
+            let mut reply = String::new();
+            let _ = stream.read_to_string(&mut reply);
+            reply
+        });
+
+        let request = server.recv().unwrap();
+        let started = Instant::now();
+        let read = read_body(request, Duration::from_millis(200), "slow-trickle-test", None);
+        assert!(matches!(read, BodyRead::Abandoned));
+        assert!(started.elapsed() < Duration::from_millis(800), "held the worker for {:?}", started.elapsed());
+
+        // The reader thread still answers the client once it stops trickling
+        let reply = client.join().unwrap();
+        assert!(reply.starts_with("HTTP/1.1 408"), "{}", reply);
+        assert!(reply.contains("body_timeout"), "{}", reply);
+    }
+
+    #[test]
+    fn put_settings_applies_the_body() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        let changed = capture_events(&app, "bridge-settings-changed");
+
+        let reply = exchange(
+            app.handle(),
+            &settings,
+            bridge_request("PUT", "/settings", &[("Content-Type", "application/json")], r#"{"risk": 2.5, "leverage": 10}"#),
+        );
+        assert_eq!(reply.status, 200, "{}", reply.body);
+        assert_eq!(reply.json()["risk"], 2.5);
+        assert_eq!(reply.json()["leverage"], 10);
+        assert!(reply.header("ETag").is_some());
+
+        let current = settings.lock().unwrap().clone();
+        assert_eq!((current.risk, current.leverage), (2.5, 10));
+        let event: Value = serde_json::from_str(&changed.recv_timeout(Duration::from_secs(1)).unwrap()).unwrap();
+        assert_eq!(event["leverage"], 10);
+
+        let invalid = exchange(
+            app.handle(),
+            &settings,
+            bridge_request("PUT", "/settings", &[("Content-Type", "application/json")], r#"{"leverage": 0}"#),
+        );
+        assert_json_error(&invalid, 422, "validation_failed");
+        assert_eq!(settings.lock().unwrap().leverage, 10);
+    }
+
+    #[test]
+    fn capabilities_describe_the_timeout_budget() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        {
+            let mut settings = settings.lock().unwrap();
+            settings.countdown_secs = 5;
+            settings.trade_timeout_secs = 60;
+        }
+
+        let reply = exchange(app.handle(), &settings, bridge_request("GET", "/capabilities", &[], ""));
+        assert_eq!(reply.status, 200, "{}", reply.body);
+        let body = reply.json();
+        assert_eq!(body["countdownSecs"], 5);
+        assert_eq!(body["tradeTimeoutSecs"], 60);
+        assert_eq!(body["resultWaitSecs"], 55);
+        assert_eq!(body["countdownInTimeout"], true);
+    }
+
+    // ---- Request tracing ----
+
+    /// Trade sent with `headers`, resolved once emitted; the reply and the event payload
+    fn traced_trade(trade_id: &str, entry: f64, headers: &'static [(&'static str, &'static str)]) -> (BridgeReply, Value) {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        let emitted = capture_events(&app, "tradingview-execute-trade");
+        let (handle, body) = (app.handle().clone(), trade_body(trade_id, entry));
+        let trade = thread::spawn(move || {
+            let mut all = vec![("Content-Type", "application/json")];
+            all.extend_from_slice(headers);
+            exchange(&handle, &settings, bridge_request("POST", "/execute-trade", &all, &body))
+        });
+        let event: Value = serde_json::from_str(&emitted.recv_timeout(Duration::from_secs(5)).unwrap()).unwrap();
+        resolve_pending_trade(trade_id, TradeResult { success: true, error: None, resolution: TradeResolution::Reported }).unwrap();
+        (trade.join().unwrap(), event)
+    }
+
+    #[test]
+    fn request_id_follows_a_trade_through_the_bridge() {
+        let (reply, event) = traced_trade("trace-test-given", 463.1, &[("X-Request-Id", "ext-463-given")]);
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.header("X-Request-Id"), Some("ext-463-given"));
+        assert_eq!(event["requestId"], "ext-463-given");
+        assert_eq!(trade_status("trace-test-given")["requestId"], "ext-463-given");
+
+        let log = get_bridge_log(Some("ext-463-given".to_string()));
+        assert!(log.iter().all(|entry| entry.request_id == "ext-463-given"), "{:?}", log);
+        assert!(log.iter().any(|entry| entry.trade_id.as_deref() == Some("trace-test-given")), "{:?}", log);
+        assert!(log.iter().any(|entry| entry.message == "POST /execute-trade -> 200"), "{:?}", log);
+
+        // Other requests are left out of the filtered log only
+        bridge_log("ext-463-other", None, "unrelated");
+        assert!(get_bridge_log(None).iter().any(|entry| entry.request_id == "ext-463-other"));
+        assert!(get_bridge_log(Some("ext-463-given".to_string())).iter().all(|entry| entry.message != "unrelated"));
+    }
+
+    #[test]
+    fn request_id_is_generated_when_missing() {
+        let (reply, event) = traced_trade("trace-test-generated", 463.2, &[]);
+        assert_eq!(reply.status, 200);
+        let request_id = reply.header("X-Request-Id").expect("generated X-Request-Id").to_string();
+        assert!(uuid::Uuid::parse_str(&request_id).is_ok(), "{}", request_id);
+        assert_eq!(event["requestId"], request_id.as_str());
+        assert_eq!(trade_status("trace-test-generated")["requestId"], request_id.as_str());
+        assert!(get_bridge_log(Some(request_id)).iter().any(|entry| entry.trade_id.as_deref() == Some("trace-test-generated")));
+    }
+
+    // ---- Conditional GET /settings ----
+
+    #[test]
+    fn settings_support_head_and_conditional_requests() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        settings.lock().unwrap().modified_at = 1_700_000_000;
+        let http_date = |secs: u64| httpdate::fmt_http_date(std::time::UNIX_EPOCH + Duration::from_secs(secs));
+        let get = |headers: &[(&str, &str)]| exchange(app.handle(), &settings, bridge_request("GET", "/settings", headers, ""));
+
+        let full = get(&[]);
+        assert_eq!(full.status, 200);
+        let etag = full.header("ETag").expect("ETag").to_string();
+        assert_eq!(full.header("Last-Modified"), Some(http_date(1_700_000_000).as_str()));
+        assert_eq!(full.json()["leverage"], 25);
+
+        // HEAD: same headers and Content-Length, no body
+        let head = exchange(app.handle(), &settings, bridge_request("HEAD", "/settings", &[], ""));
+        assert_eq!(head.status, 200);
+        assert_eq!(head.header("ETag"), Some(etag.as_str()));
+        assert_eq!(head.header("Content-Length"), Some(full.body.len().to_string().as_str()));
+        assert!(head.body.is_empty(), "{:?}", head.body);
+
+        let current = http_date(1_700_000_000);
+        let stale = http_date(1_699_990_000);
+        let cases: [(&[(&str, &str)], u16); 6] = [
+            (&[("If-None-Match", etag.as_str())], 304),
+            (&[("If-None-Match", "\"some-other-tag\", *")], 304),
+            (&[("If-None-Match", "\"some-other-tag\"")], 200),
+            (&[("If-Modified-Since", current.as_str())], 304),
+            (&[("If-Modified-Since", stale.as_str())], 200),
+            // A changed ETag wins over an up-to-date date
+            (&[("If-None-Match", "\"some-other-tag\""), ("If-Modified-Since", current.as_str())], 200),
+        ];
+        for (headers, status) in cases {
+            let reply = get(headers);
+            assert_eq!(reply.status, status, "{:?}", headers);
+            assert_eq!(reply.header("ETag"), Some(etag.as_str()), "{:?}", headers);
+            if status == 304 {
+                assert!(reply.body.is_empty(), "{:?}: {:?}", headers, reply.body);
+            } else {
+                assert_eq!(reply.body, full.body, "{:?}", headers);
+            }
+        }
+    }
+
+    // ---- Trade deduplication ----
+
+    #[test]
+    fn dedup_key_covers_every_trade_parameter() {
+        let base = || {
+            let mut trade = sample_trade();
+            trade.entry = 512.0;
+            trade
+        };
+        let claim = |trade: &TradeRequest, asset: &str, trade_id: &str| claim_trade_key(None, trade, asset, trade_id, 60);
+
+        let first = claim(&base(), "BTC", "dedup-test-first").unwrap();
+        assert!(first.is_some());
+        assert_eq!(claim(&base(), "BTC", "dedup-test-repeat"), Err("dedup-test-first".to_string()));
+
+        // Trades that differ in anything that gets placed are not duplicates
+        let mut other_tp = base();
+        other_tp.take_profit = Some(120.0);
+        let mut no_tp = base();
+        no_tp.take_profit = None;
+        let mut other_strategy = base();
+        other_strategy.strategy = Some("breakout".to_string());
+        for (name, trade, asset) in [("asset", base(), "ETH"), ("take profit", other_tp, "BTC"), ("no take profit", no_tp, "BTC"), ("strategy", other_strategy, "BTC")] {
+            assert!(claim(&trade, asset, &format!("dedup-test-{}", name)).is_ok(), "{}", name);
+        }
+
+        // Only the trade holding the key can give it back
+        release_trade_key(first.as_deref(), "dedup-test-repeat");
+        assert_eq!(claim(&base(), "BTC", "dedup-test-repeat"), Err("dedup-test-first".to_string()));
+        release_trade_key(first.as_deref(), "dedup-test-first");
+        assert!(claim(&base(), "BTC", "dedup-test-retry").is_ok());
+    }
+
+    // ---- CORS ----
+
+    #[test]
+    fn allow_origin_is_only_sent_to_allowed_browser_origins() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        let health = |headers: &[(&str, &str)]| exchange(app.handle(), &settings, bridge_request("GET", "/health", headers, ""));
+
+        let browser = health(&[("Origin", "https://www.tradingview.com")]);
+        assert_eq!(browser.status, 200);
+        assert_eq!(browser.header("Access-Control-Allow-Origin"), Some("https://www.tradingview.com"));
+
+        // Not a CORS request: no Allow-Origin at all, rather than "null"
+        let local = health(&[]);
+        assert_eq!(local.status, 200);
+        assert_eq!(local.header("Access-Control-Allow-Origin"), None, "{}", local.head);
+
+        let foreign = health(&[("Origin", "https://evil.example")]);
+        assert_eq!(foreign.status, 403);
+        assert_eq!(foreign.header("Access-Control-Allow-Origin"), None, "{}", foreign.head);
+    }
+
+    // ---- Shutdown ----
+
+    #[test]
+    fn trades_pending_at_shutdown_are_reported_as_interrupted() {
+        let (tx, rx) = channel();
+        let mut pending = std::collections::HashMap::new();
+        pending.insert(
+            "shutdown-test-trade".to_string(),
+            PendingTrade { request: sample_trade(), sender: tx, started_at: Instant::now(), request_id: "shutdown-test-request".to_string(), executing: true },
+        );
+
+        let interrupted = interrupt_pending_trades(&mut pending);
+        assert!(pending.is_empty());
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(interrupted[0].1.trade_id.as_deref(), Some("shutdown-test-trade"));
+
+        // The waiting request is woken, and a later status poll knows what happened
+        assert_eq!(rx.try_recv().unwrap().resolution, TradeResolution::Interrupted);
+        let status = trade_status("shutdown-test-trade");
+        assert_eq!(status["status"], "interrupted");
+        assert_eq!(status["requestId"], "shutdown-test-request");
+    }
+
+    // ---- Fault injection ----
+
+    #[test]
+    fn fault_injection_header_is_only_sent_by_development_builds() {
+        let app = tauri::test::mock_app();
+        let settings = test_settings();
+        for path in ["/health", "/settings"] {
+            let reply = exchange(app.handle(), &settings, bridge_request("GET", path, &[], ""));
+            assert_eq!(reply.status, 200, "{}", path);
+            let expected = if cfg!(debug_assertions) { Some("off") } else { None };
+            assert_eq!(reply.header("X-Fault-Injection"), expected, "{}", path);
+        }
+    }
 }
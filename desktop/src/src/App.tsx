@@ -25,6 +25,9 @@ import {
   useAppStore,
   type TVPositionData,
   type TradeHistoryItem,
+  type InterruptedTradesMarker,
+  type TradeCountdown,
+  type CrashReport,
 } from "./stores";
 
 interface TVTradeRequest {
@@ -34,6 +37,7 @@ interface TVTradeRequest {
   takeProfit: number | null;
   risk: number;
   leverage: number;
+  tradeId: string;
 }
 
 // ==================== LOGGING SYSTEM ====================
@@ -483,11 +487,16 @@ function App() {
     tvPosition, setTvPosition,
     tvOverlayVisible, setTvOverlayVisible,
     pendingExtensionTrade, setPendingExtensionTrade,
+    bridgeToken, setBridgeToken,
+    bridgeStatus, setBridgeStatus,
+    interruptedTrades, setInterruptedTrades,
+    tradeCountdowns, updateTradeCountdown,
     tradeHistory, setTradeHistory,
     updateAvailable, setUpdateAvailable,
     isUpdating, setIsUpdating,
     showVpnWarning, setShowVpnWarning,
     vpnWarningDismissed, setVpnWarningDismissed,
+    crashReport, setCrashReport,
     showWithdrawModal, setShowWithdrawModal,
     withdrawDestination, setWithdrawDestination,
     withdrawing, setWithdrawing,
@@ -542,10 +551,63 @@ function App() {
     unfilledOrder, setUnfilledOrder,
   } = useTradeStore();
   const tradeHistoryRef = useRef<TradeHistoryItem[]>([]);
-  // Ref for deduplicating trade requests (survives React StrictMode double-mount)
-  const lastTradeTimestampRef = useRef<number>(0);
-  // Ref for content-based deduplication (prevents retries from extension)
-  const lastTradeParamsRef = useRef<string>("");
+  // Bridge trades waiting on a result, by tradeId in arrival order. Only one is in the form at a
+  // time; the rest are loaded one by one as results are reported.
+  const extensionTradesRef = useRef<Map<string, TVTradeRequest>>(new Map());
+  // Bridge trade id the current execution reports its result to (null for manual trades)
+  const extensionTradeIdRef = useRef<string | null>(null);
+
+  // Fill the form with a bridge trade and confirm or auto-execute it
+  const loadExtensionTrade = (trade: TVTradeRequest) => {
+    extensionTradeIdRef.current = trade.tradeId;
+
+    // Fill in the form values
+    setDirection(trade.direction as "long" | "short");
+    setEntryPrice(trade.entry.toString());
+    setStopLoss(trade.stopLoss.toString());
+    setTakeProfit(trade.takeProfit ? trade.takeProfit.toString() : "");
+    setRiskAmount(trade.risk.toString());
+    setLeverage(trade.leverage.toString());
+
+    // Auto-disable entry update since we have a specific entry
+    setAutoUpdateEntry(false);
+
+    // Check if we should skip confirmation (execute directly)
+    if (settingsRef.current.extensionSkipConfirm) {
+      console.log("[TVBridge] Skipping confirmation - auto-executing trade");
+      // Set flag to trigger auto-execution after state updates
+      setPendingExtensionTrade(true);
+    } else {
+      // Show the confirmation modal
+      setShowConfirmModal(true);
+    }
+  };
+
+  // Load the oldest queued bridge trade unless one is already in the form
+  const loadNextExtensionTrade = () => {
+    if (extensionTradeIdRef.current) return;
+    const next = extensionTradesRef.current.values().next();
+    if (!next.done) loadExtensionTrade(next.value);
+  };
+
+  // Report the result to the waiting extension request once; later reports are no-ops
+  const reportExtensionTradeResult = (success: boolean, error: string | null) => {
+    const tradeId = extensionTradeIdRef.current;
+    if (!tradeId) return;
+    extensionTradeIdRef.current = null;
+    extensionTradesRef.current.delete(tradeId);
+    invoke("report_trade_result", { tradeId, success, error }).catch((e) => {
+      log.debug("Trading", "Failed to report trade result (extension not waiting)", e);
+    });
+    // Mid-execution the next trade waits until the form is free again (see the isExecuting effect)
+    if (!useTradeStore.getState().isExecuting) loadNextExtensionTrade();
+  };
+
+  // Closing the confirmation is an answer too, so the extension isn't left waiting for the timeout
+  const dismissConfirmModal = () => {
+    setShowConfirmModal(false);
+    reportExtensionTradeResult(false, "Trade cancelled in the app");
+  };
 
   // Advanced settings - from Zustand store
   const {
@@ -565,6 +627,8 @@ function App() {
     settingsLoaded, setSettingsLoaded,
     extensionSkipConfirm, setExtensionSkipConfirm,
     extensionEnabled, setExtensionEnabled,
+    countdownSecs, setCountdownSecs,
+    bridgePort, setBridgePort,
   } = useSettingsStore();
 
   // App update state & TradingView Bridge state now come from useAppStore above
@@ -597,6 +661,35 @@ function App() {
     }
   }, [vpnWarningDismissed, setShowVpnWarning]);
 
+  // Crash report - the last session panicked; offer it on the next start
+  useEffect(() => {
+    invoke<CrashReport | null>("get_last_crash_report")
+      .then((report) => {
+        if (report) {
+          log.warn("Crash", "Previous session crashed", { message: report.message, location: report.location });
+          setCrashReport(report);
+        }
+      })
+      .catch((e) => log.error("Crash", "Failed to read crash report", e));
+  }, [setCrashReport]);
+
+  // Interrupted trades - the last session quit while a bridge trade was unresolved.
+  // The marker is consumed on read, so only take it once the user can act on it.
+  const interruptedTradesCheckedRef = useRef(false);
+  useEffect(() => {
+    if (appState !== "dashboard" || interruptedTradesCheckedRef.current) return;
+    interruptedTradesCheckedRef.current = true;
+
+    invoke<InterruptedTradesMarker | null>("take_pending_trade_marker")
+      .then((marker) => {
+        if (marker && marker.requests.length > 0) {
+          log.warn("Bridge", "Previous session quit with unresolved trades", marker);
+          setInterruptedTrades(marker);
+        }
+      })
+      .catch((e) => log.error("Bridge", "Failed to read pending trade marker", e));
+  }, [appState, setInterruptedTrades]);
+
   // Debug: Log overlay visibility changes
   useEffect(() => {
     console.log("[TVBridge DEBUG] tvOverlayVisible changed to:", tvOverlayVisible, "tvPosition:", tvPosition);
@@ -624,6 +717,8 @@ function App() {
     feeBuffer: number;
     extensionSkipConfirm: boolean;
     extensionEnabled: boolean;
+    countdownSecs: number;
+    bridgePort: number;
   }
 
   // Ref to track pending save timeout for debouncing
@@ -635,7 +730,7 @@ function App() {
     sidebarPosition, googleSheetsUrl, autoAdjustLeverage, autoRetryUnfilled,
     liqWarningDistance, liqDangerDistance, pnlTolerance, updateEntryOnConfirm,
     copyReportToClipboard, debugLogging, unfilledWaitTime, maxRiskMultiplier, feeBuffer,
-    extensionSkipConfirm, extensionEnabled,
+    extensionSkipConfirm, extensionEnabled, countdownSecs, bridgePort,
   });
 
   // Keep ref in sync
@@ -644,7 +739,7 @@ function App() {
     sidebarPosition, googleSheetsUrl, autoAdjustLeverage, autoRetryUnfilled,
     liqWarningDistance, liqDangerDistance, pnlTolerance, updateEntryOnConfirm,
     copyReportToClipboard, debugLogging, unfilledWaitTime, maxRiskMultiplier, feeBuffer,
-    extensionSkipConfirm, extensionEnabled,
+    extensionSkipConfirm, extensionEnabled, countdownSecs, bridgePort,
   };
 
   // Save settings to store (stable callback, reads from ref)
@@ -688,6 +783,8 @@ function App() {
         setFeeBuffer(settings.feeBuffer ?? 0.05);
         setExtensionSkipConfirm(settings.extensionSkipConfirm ?? true);
         setExtensionEnabled(settings.extensionEnabled ?? true);
+        setCountdownSecs(settings.countdownSecs ?? 0);
+        setBridgePort(settings.bridgePort ?? 3456);
       }
 
       // Load persisted open trades
@@ -766,7 +863,7 @@ function App() {
     sidebarPosition, googleSheetsUrl, autoAdjustLeverage, autoRetryUnfilled,
     liqWarningDistance, liqDangerDistance, pnlTolerance, updateEntryOnConfirm,
     copyReportToClipboard, debugLogging, unfilledWaitTime, maxRiskMultiplier, feeBuffer,
-    extensionSkipConfirm, extensionEnabled
+    extensionSkipConfirm, extensionEnabled, countdownSecs, bridgePort
   ]);
 
   // Sync settings with TradingView Bridge backend
@@ -778,9 +875,12 @@ function App() {
       risk,
       leverage: lev,
       asset: selectedAsset,
-      price: currentPrice
+      price: currentPrice,
+      countdownSecs,
+      // Changing it restarts the bridge listener (a no-op while unchanged)
+      bridgePort: bridgePort >= 1024 ? bridgePort : null,
     }).catch(() => {});
-  }, [riskAmount, leverage, selectedAsset, prices]);
+  }, [riskAmount, leverage, selectedAsset, prices, countdownSecs, bridgePort]);
 
   // Load settings when wallet is available
   useEffect(() => {
@@ -1295,6 +1395,11 @@ function App() {
     let unlistenPosition: (() => void) | null = null;
     let unlistenClosed: (() => void) | null = null;
     let unlistenExecute: (() => void) | null = null;
+    let unlistenCancel: (() => void) | null = null;
+    let unlistenRejected: (() => void) | null = null;
+    let unlistenSettings: (() => void) | null = null;
+    let unlistenCountdown: (() => void) | null = null;
+    let unlistenCountdownAborted: (() => void) | null = null;
 
     const setupListeners = async () => {
       console.log("[TVBridge] Setting up event listeners...");
@@ -1306,79 +1411,102 @@ function App() {
       });
       console.log("[TVBridge] Position listener ready");
 
-      unlistenClosed = await listen("tradingview-position-closed", () => {
+      // Stale or future-dated positions the bridge dropped instead of overlaying
+      unlistenRejected = await listen<{ reason: string; requestId: string }>("tradingview-position-rejected", (event) => {
+        log.warn("TVBridge", `Position rejected: ${event.payload.reason}`, event.payload);
+      });
+
+      unlistenClosed = await listen<{ asset: string | null }>("tradingview-position-closed", (event) => {
+        // Closing another chart's position leaves the current overlay alone
+        const closedAsset = event.payload?.asset;
+        const current = useAppStore.getState().tvPosition;
+        if (closedAsset && current?.asset && current.asset !== closedAsset) {
+          return;
+        }
         console.log("[TVBridge] *** POSITION CLOSED ***");
         log.info("TVBridge", "Position closed");
         setTvOverlayVisible(false);
         setTvPosition(null);
       });
 
-      // Execute trade from TradingView Bridge extension
-      // Deduplication uses ref to survive React StrictMode double-mount
-      unlistenExecute = await listen<TVTradeRequest>("tradingview-execute-trade", (event) => {
-        const now = Date.now();
-
-        // Time-based deduplication: ignore if received within 2 seconds
-        if (now - lastTradeTimestampRef.current < 2000) {
-          console.log("[TVBridge] Ignoring duplicate trade request (within 2s)");
-          return;
+      // Grace countdown before an auto-executed trade reaches us; abortable from the banner
+      unlistenCountdown = await listen<TradeCountdown>("trade-countdown", (event) => {
+        if (!useAppStore.getState().tradeCountdowns[event.payload.tradeId]) {
+          log.info("TVBridge", `Trade countdown started (${event.payload.remaining}s)`, event.payload);
         }
+        updateTradeCountdown(event.payload.tradeId, event.payload);
+      });
 
-        // Content-based deduplication: ignore if same trade params within 60 seconds
-        // This prevents TradingView extension retries from creating duplicate trades
-        const tradeParams = JSON.stringify({
-          direction: event.payload.direction,
-          entry: event.payload.entry,
-          stopLoss: event.payload.stopLoss,
-          takeProfit: event.payload.takeProfit,
-        });
-        if (tradeParams === lastTradeParamsRef.current && now - lastTradeTimestampRef.current < 60000) {
-          console.log("[TVBridge] Ignoring retry - same trade params within 60s");
-          // Report success to stop extension from retrying
-          invoke("report_trade_result", { success: true, error: null }).catch(() => {});
+      unlistenCountdownAborted = await listen<{ tradeId: string }>("trade-countdown-aborted", (event) => {
+        log.info("TVBridge", "Trade countdown aborted", event.payload);
+        updateTradeCountdown(event.payload.tradeId, null);
+      });
+
+      // Execute trade from TradingView Bridge extension
+      unlistenExecute = await listen<TVTradeRequest>("tradingview-execute-trade", (event) => {
+        // The same event delivered twice (React StrictMode double-mount) is one trade
+        if (extensionTradesRef.current.has(event.payload.tradeId)) {
+          console.log("[TVBridge] Ignoring repeated event for trade", event.payload.tradeId);
           return;
         }
 
-        lastTradeTimestampRef.current = now;
-        lastTradeParamsRef.current = tradeParams;
-
+        // Retries of the same trade are caught by the bridge (Idempotency-Key and
+        // duplicate_trade_window_secs) with a 409, so every trade that gets here is real
         console.log("[TVBridge] *** EXECUTE TRADE ***", event.payload);
+        // The countdown (if any) is over once the trade is handed to us
+        updateTradeCountdown(event.payload.tradeId, null);
         log.info("TVBridge", "Execute trade request", event.payload);
 
         // Check if extension is enabled
         if (!settingsRef.current.extensionEnabled) {
           console.log("[TVBridge] Extension disabled - ignoring trade request");
           // Report back that extension is disabled
-          invoke("report_trade_result", { success: false, error: "Extension disabled in app settings" }).catch(() => {});
+          invoke("report_trade_result", { tradeId: event.payload.tradeId, success: false, error: "Extension disabled in app settings" }).catch(() => {});
           return;
         }
 
-        const { direction, entry, stopLoss, takeProfit, risk, leverage: lev } = event.payload;
-
-        // Fill in the form values
-        setDirection(direction as "long" | "short");
-        setEntryPrice(entry.toString());
-        setStopLoss(stopLoss.toString());
-        setTakeProfit(takeProfit ? takeProfit.toString() : "");
-        setRiskAmount(risk.toString());
-        setLeverage(lev.toString());
-
-        // Auto-disable entry update since we have a specific entry
-        setAutoUpdateEntry(false);
-
-        // Check if we should skip confirmation (execute directly)
-        if (settingsRef.current.extensionSkipConfirm) {
-          console.log("[TVBridge] Skipping confirmation - auto-executing trade");
-          // Set flag to trigger auto-execution after state updates
-          setPendingExtensionTrade(true);
-        } else {
-          // Show the confirmation modal
-          setShowConfirmModal(true);
+        // Queued behind any trade still in the form; each one gets its own result
+        extensionTradesRef.current.set(event.payload.tradeId, event.payload);
+        if (extensionTradeIdRef.current || useTradeStore.getState().isExecuting) {
+          log.info("TVBridge", "Trade queued behind the current one", { tradeId: event.payload.tradeId, queued: extensionTradesRef.current.size });
         }
+        loadNextExtensionTrade();
       });
       console.log("[TVBridge] Execute trade listener ready");
 
+      // Risk/leverage/asset changed from the TradingView side (PUT /settings)
+      unlistenSettings = await listen<{ risk: number; leverage: number; asset: string }>("bridge-settings-changed", (event) => {
+        const { risk, leverage: lev, asset } = event.payload;
+        log.info("TVBridge", "Settings changed from extension", event.payload);
+        const state = useTradeStore.getState();
+        if (parseFloat(state.riskAmount) !== risk) setRiskAmount(risk.toFixed(2));
+        if (parseInt(state.leverage) !== lev) setLeverage(lev.toString());
+        if (state.selectedAsset !== asset) setSelectedAsset(asset);
+      });
+
+      // Trade cancelled from the extension. The bridge refuses cancels once a trade has been
+      // sent to us (409 already_executing), so this only ends a countdown, or a trade still
+      // waiting for confirmation - never one that is placing orders.
+      unlistenCancel = await listen<{ tradeId: string }>("tradingview-cancel-trade", (event) => {
+        const { tradeId } = event.payload;
+        updateTradeCountdown(tradeId, null);
+        if (!extensionTradesRef.current.has(tradeId)) return;
+        if (extensionTradeIdRef.current === tradeId && useTradeStore.getState().isExecuting) return;
+        // The bridge already answered the extension; just drop the trade here
+        extensionTradesRef.current.delete(tradeId);
+        console.log("[TVBridge] *** TRADE CANCELLED ***", tradeId);
+        log.info("TVBridge", "Trade cancelled from extension", event.payload);
+        if (extensionTradeIdRef.current !== tradeId) return;
+        extensionTradeIdRef.current = null;
+        setPendingExtensionTrade(false);
+        setShowConfirmModal(false);
+        setError("Trade cancelled from TradingView");
+        loadNextExtensionTrade();
+      });
+
       console.log("[TVBridge] All listeners ready");
+      // Reported by the bridge's /health endpoint
+      invoke("set_frontend_ready", { ready: true }).catch(() => {});
     };
 
     setupListeners();
@@ -1387,6 +1515,12 @@ function App() {
       if (unlistenPosition) unlistenPosition();
       if (unlistenClosed) unlistenClosed();
       if (unlistenExecute) unlistenExecute();
+      if (unlistenCancel) unlistenCancel();
+      if (unlistenRejected) unlistenRejected();
+      if (unlistenSettings) unlistenSettings();
+      if (unlistenCountdown) unlistenCountdown();
+      if (unlistenCountdownAborted) unlistenCountdownAborted();
+      invoke("set_frontend_ready", { ready: false }).catch(() => {});
     };
   }, []);
 
@@ -1544,6 +1678,48 @@ function App() {
     }
   }, []);
 
+  // Bridge port and start errors. The bridge starts before anything here listens, so the
+  // current state is queried once the listeners are up; the events cover later restarts.
+  useEffect(() => {
+    let unlistenStarted: (() => void) | null = null;
+    let unlistenError: (() => void) | null = null;
+
+    const setupListeners = async () => {
+      unlistenStarted = await listen<{ port: number; fallback: boolean }>("bridge-started", (event) => {
+        log.info("TVBridge", `Bridge listening on port ${event.payload.port}`, event.payload);
+        setBridgeStatus({ port: event.payload.port, error: null });
+      });
+      unlistenError = await listen<{ error: string }>("bridge-error", (event) => {
+        log.error("TVBridge", "Bridge failed to start", event.payload);
+        setBridgeStatus({ port: null, error: event.payload.error });
+        setError("TradingView bridge failed to start: " + event.payload.error);
+      });
+
+      try {
+        const port = await invoke<number | null>("get_bridge_port");
+        const binding = await invoke<{ error: string | null }>("get_bridge_binding");
+        setBridgeStatus({ port, error: port ? null : binding.error });
+      } catch (e) {
+        log.error("TVBridge", "Failed to read bridge status", e);
+      }
+    };
+
+    setupListeners();
+
+    return () => {
+      if (unlistenStarted) unlistenStarted();
+      if (unlistenError) unlistenError();
+    };
+  }, [setBridgeStatus, setError]);
+
+  // Load the extension pairing code when settings open
+  useEffect(() => {
+    if (!showSettings) return;
+    invoke<string>("get_bridge_token")
+      .then(setBridgeToken)
+      .catch((e) => console.error("[TVBridge] Failed to load pairing code:", e));
+  }, [showSettings]);
+
   // Restore scroll position after settings panel render
   useLayoutEffect(() => {
     if (showSettings && settingsScrollRef.current && settingsScrollTop.current > 0) {
@@ -2249,9 +2425,7 @@ function App() {
 
       // Report success to extension EARLY (before position verification)
       log.info("Trading", "Orders placed successfully, reporting to extension");
-      invoke("report_trade_result", { success: true, error: null }).catch((e) => {
-        log.debug("Trading", "Early success report failed (extension not waiting)", e);
-      });
+      reportExtensionTradeResult(true, null);
 
       // Generate timestamp for trade identification
       const sheetsTimestamp = new Date().toISOString();
@@ -2355,9 +2529,7 @@ function App() {
       setExecutionStatus("Trade placed successfully!");
 
       // Report success to extension via Tauri
-      invoke("report_trade_result", { success: true, error: null }).catch((e) => {
-        log.debug("Trading", "Failed to report trade result (extension not waiting)", e);
-      });
+      reportExtensionTradeResult(true, null);
 
       // Reset auto-update entry for next trade
       setAutoUpdateEntry(true);
@@ -2367,9 +2539,7 @@ function App() {
       setExecutionStatus(`Error: ${errorMsg}`);
 
       // Report failure to extension via Tauri
-      invoke("report_trade_result", { success: false, error: errorMsg }).catch((err) => {
-        log.debug("Trading", "Failed to report trade result (extension not waiting)", err);
-      });
+      reportExtensionTradeResult(false, errorMsg);
 
       // Check if this might be an unfilled order scenario (not a validation error)
       // Don't show retry modal for validation errors like minimum order size
@@ -2397,6 +2567,11 @@ function App() {
     }
   };
 
+  // A bridge trade queued during an execution is loaded once it finishes
+  useEffect(() => {
+    if (!isExecuting) loadNextExtensionTrade();
+  }, [isExecuting]);
+
   // Auto-execute trade from extension (when pendingExtensionTrade is set)
   useEffect(() => {
     if (pendingExtensionTrade && calculatedQty && entryPrice && direction && !isExecuting) {
@@ -2728,11 +2903,91 @@ function App() {
     );
   };
 
+  // Crash report prompt - shows on ALL screens after a crash in the previous session
+  const dismissCrashReport = () => {
+    setCrashReport(null);
+    invoke("dismiss_crash_report").catch((e) => log.error("Crash", "Failed to dismiss crash report", e));
+  };
+
+  const crashReportText = (report: CrashReport) => [
+    `Version: ${report.version}`,
+    `Time: ${new Date(report.timestamp).toISOString()}`,
+    `Thread: ${report.thread}`,
+    `Panic: ${report.message}`,
+    `Location: ${report.location ?? "unknown"}`,
+    "",
+    report.backtrace,
+  ].join("\n");
+
+  // Rendered as an element (not a component) so the details toggle survives re-renders
+  const crashReportModal = crashReport && (
+    <div className="modal-overlay">
+      <div className="modal retry-modal">
+        <div className="modal-title warning-title">
+          The App Crashed Last Time
+        </div>
+
+        <div className="modal-body">
+          <div className="retry-info">
+            <p>
+              Version {crashReport.version} stopped unexpectedly on {new Date(crashReport.timestamp).toLocaleString()}:
+              <strong> {crashReport.message}</strong>
+            </p>
+            <p>
+              Save a diagnostics file (crash report and recent logs) to Documents to send with a bug report.
+              It contains no keys or passwords.
+            </p>
+            <details>
+              <summary>View details</summary>
+              <pre className="crash-report-details">{crashReportText(crashReport)}</pre>
+            </details>
+          </div>
+        </div>
+
+        <div className="modal-buttons">
+          <button className="modal-btn cancel" onClick={dismissCrashReport}>
+            Dismiss
+          </button>
+          <button
+            className="modal-btn cancel"
+            onClick={async () => {
+              try {
+                await writeText(crashReportText(crashReport));
+                setSuccess("Crash report copied to clipboard");
+              } catch (e) {
+                setError("Failed to copy crash report: " + getErrorMessage(e));
+              }
+            }}
+          >
+            Copy
+          </button>
+          <button
+            className="modal-btn confirm"
+            onClick={async () => {
+              try {
+                const fileName = `hyperliquid-trader-diagnostics-${crashReport.timestamp}.json`;
+                const diagnostics = { crashReport, logs: log.getHistory() };
+                await writeTextFile(fileName, JSON.stringify(diagnostics, null, 2), { baseDir: BaseDirectory.Document });
+                setSuccess(`Diagnostics saved to Documents/${fileName}`);
+                dismissCrashReport();
+              } catch (e) {
+                setError("Failed to save diagnostics: " + getErrorMessage(e));
+              }
+            }}
+          >
+            Save Diagnostics
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+
   // Loading state
   if (appState === "loading") {
     return (
       <>
         <VpnWarningModal />
+        {crashReportModal}
         <AuthLayout title="Loading" subtitle="Initializing secure vault...">
           <div style={{ textAlign: "center", padding: "40px 0" }}>
             <div className="biometric-icon" style={{ margin: "0 auto 16px" }}>
@@ -2750,6 +3005,7 @@ function App() {
     return (
       <>
         <VpnWarningModal />
+        {crashReportModal}
         <AuthLayout
         title="Touch ID"
         subtitle="Use biometrics to unlock your vault"
@@ -2785,6 +3041,7 @@ function App() {
     return (
       <>
         <VpnWarningModal />
+        {crashReportModal}
         <AuthLayout
         title="Create Your Vault"
         subtitle="Set up a password to protect your trading keys"
@@ -2854,6 +3111,7 @@ function App() {
     return (
       <>
         <VpnWarningModal />
+        {crashReportModal}
         <AuthLayout
         title="Welcome Back"
         subtitle={biometricFailed ? "Touch ID failed. Enter your password." : "Enter your password to unlock"}
@@ -2919,6 +3177,7 @@ function App() {
     return (
       <>
         <VpnWarningModal />
+        {crashReportModal}
         <AuthLayout
         title="Connect Wallet"
         subtitle={`Connect to ${exchangeConfig.name} to start trading`}
@@ -3303,6 +3562,39 @@ function App() {
             <div className="tv-bridge-tip">
               <strong>Tip:</strong> Use Bybit perpetual charts (e.g., <code>BYBIT:BTCUSDT.P</code>) for prices closest to Hyperliquid.
             </div>
+            <div className="tv-bridge-tip tv-bridge-pairing">
+              <strong>Pairing code:</strong> <code>{bridgeToken ? `${bridgeToken.slice(0, 8)}...` : "---"}</code>
+              <div className="tv-bridge-pairing-desc">
+                The extension asks for this code the first time it connects.
+              </div>
+              <div className="tv-bridge-pairing-actions">
+                <button
+                  disabled={!bridgeToken}
+                  onClick={async () => {
+                    try {
+                      await writeText(bridgeToken);
+                      setSuccess("Pairing code copied!");
+                    } catch (e) {
+                      setError("Failed to copy pairing code: " + getErrorMessage(e));
+                    }
+                  }}
+                >
+                  Copy
+                </button>
+                <button
+                  onClick={async () => {
+                    try {
+                      setBridgeToken(await invoke<string>("rotate_bridge_token"));
+                      setSuccess("New pairing code generated - re-pair the extension");
+                    } catch (e) {
+                      setError("Failed to regenerate pairing code: " + getErrorMessage(e));
+                    }
+                  }}
+                >
+                  Regenerate
+                </button>
+              </div>
+            </div>
             <div className="settings-toggles tv-bridge-toggles">
               <label className="toggle-item">
                 <input
@@ -3321,6 +3613,43 @@ function App() {
                 <span>Execute trades directly (skip confirmation)</span>
               </label>
             </div>
+            <div className={`tv-bridge-tip ${bridgeStatus.error ? "tv-bridge-error" : ""}`}>
+              {bridgeStatus.port ? (
+                <>
+                  <strong>Bridge port:</strong> <code>{bridgeStatus.port}</code>
+                  {bridgePort >= 1024 && bridgeStatus.port !== bridgePort && ` (preferred port ${bridgePort} unavailable)`}
+                </>
+              ) : bridgeStatus.error ? (
+                <><strong>Bridge not running:</strong> {bridgeStatus.error}</>
+              ) : (
+                <><strong>Bridge port:</strong> starting...</>
+              )}
+              <div className="tv-bridge-pairing-desc">
+                The extension finds ports 3456-3466 on its own and asks for the port otherwise.
+              </div>
+            </div>
+            <div className="setting-item full-width">
+              <label>Preferred bridge port</label>
+              <input
+                type="number"
+                value={bridgePort}
+                onChange={(e) => setBridgePort(parseInt(e.target.value) || 0)}
+                min="1024"
+                max="65525"
+                step="1"
+              />
+            </div>
+            <div className="setting-item full-width">
+              <label>Countdown before execution (sec, 0 = off)</label>
+              <input
+                type="number"
+                value={countdownSecs}
+                onChange={(e) => setCountdownSecs(Math.min(30, Math.max(0, parseInt(e.target.value) || 0)))}
+                min="0"
+                max="30"
+                step="1"
+              />
+            </div>
           </div>
         </div>
       )}
@@ -3781,6 +4110,29 @@ function App() {
 
       {/* VPN Warning Modal - shows on all screens */}
       <VpnWarningModal />
+      {crashReportModal}
+
+      {/* Grace countdowns for incoming bridge trades */}
+      {Object.values(tradeCountdowns).map((countdown) => (
+        <div key={countdown.tradeId} className="update-banner countdown-banner">
+          <span>
+            {countdown.trade.direction.toUpperCase()} at ${countdown.trade.entry} (SL ${countdown.trade.stopLoss}) executes
+            in {countdown.remaining}s
+          </span>
+          <button
+            className="update-btn"
+            onClick={() => {
+              invoke<boolean>("abort_countdown", { tradeId: countdown.tradeId })
+                .then((aborted) => {
+                  if (!aborted) setError("Too late to abort - the trade is already executing");
+                })
+                .catch((e) => setError("Failed to abort trade: " + getErrorMessage(e)));
+            }}
+          >
+            Abort
+          </button>
+        </div>
+      ))}
 
       {sidebarPosition === "left" ? (
         <>
@@ -3796,7 +4148,7 @@ function App() {
 
       {/* Confirmation Modal */}
       {showConfirmModal && calculatedQty && direction && (
-        <div className="modal-overlay" onClick={() => setShowConfirmModal(false)}>
+        <div className="modal-overlay" onClick={dismissConfirmModal}>
           <div className="modal" onClick={(e) => e.stopPropagation()}>
             <div className={`modal-title ${direction}`}>
               Confirm {direction.toUpperCase()} {selectedAsset}
@@ -3931,7 +4283,7 @@ function App() {
             <div className="modal-buttons">
               <button
                 className="modal-btn cancel"
-                onClick={() => setShowConfirmModal(false)}
+                onClick={dismissConfirmModal}
               >
                 Cancel
               </button>
@@ -4095,6 +4447,48 @@ function App() {
         </div>
       )}
 
+      {/* Interrupted Trades Modal - the last session quit mid-trade */}
+      {interruptedTrades && (
+        <div className="modal-overlay">
+          <div className="modal retry-modal">
+            <div className="modal-title warning-title">
+              Trades Interrupted
+            </div>
+
+            <div className="modal-body">
+              <div className="retry-info">
+                <p>
+                  The app was closed at {new Date(interruptedTrades.interruptedAt * 1000).toLocaleString()} while
+                  {interruptedTrades.requests.length === 1 ? " a TradingView trade was" : ` ${interruptedTrades.requests.length} TradingView trades were`} still
+                  in progress. Orders may have been partially placed - check your positions and open orders.
+                </p>
+                {interruptedTrades.requests.map((trade, i) => (
+                  <p key={trade.tradeId || i}>
+                    <strong>{trade.direction.toUpperCase()}</strong> entry ${trade.entry} / SL ${trade.stopLoss}
+                    {trade.takeProfit ? ` / TP $${trade.takeProfit}` : ""}
+                  </p>
+                ))}
+              </div>
+            </div>
+
+            <div className="modal-buttons">
+              <button className="modal-btn cancel" onClick={() => setInterruptedTrades(null)}>
+                Dismiss
+              </button>
+              <button
+                className="modal-btn confirm"
+                onClick={() => {
+                  setInterruptedTrades(null);
+                  setActiveTab("orders");
+                }}
+              >
+                Review Orders
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
       {error && <div className="toast error">{error}</div>}
       {success && <div className="toast success">{success}</div>}
 
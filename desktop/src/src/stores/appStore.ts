@@ -8,6 +8,41 @@ interface TVPositionData {
   stopLoss: number;
   takeProfit: number | null;
   timestamp: number;
+  asset?: string;
+}
+
+// Marker left by a shutdown that interrupted bridge trades (see take_pending_trade_marker)
+interface InterruptedTradesMarker {
+  requests: {
+    direction: "long" | "short";
+    entry: number;
+    stopLoss: number;
+    takeProfit?: number | null;
+    tradeId?: string;
+  }[];
+  interruptedAt: number;
+}
+
+// Grace countdown running in the bridge before a trade is executed
+interface TradeCountdown {
+  tradeId: string;
+  remaining: number;
+  trade: {
+    direction: "long" | "short";
+    entry: number;
+    stopLoss: number;
+    takeProfit?: number | null;
+  };
+}
+
+// Left by a panic in the previous session (see get_last_crash_report)
+interface CrashReport {
+  timestamp: number;
+  version: string;
+  thread: string;
+  message: string;
+  location: string | null;
+  backtrace: string;
 }
 
 interface TradeHistoryItem {
@@ -62,6 +97,11 @@ interface AppStoreState {
   tvPosition: TVPositionData | null;
   tvOverlayVisible: boolean;
   pendingExtensionTrade: boolean;
+  bridgeToken: string;
+  // Port the bridge actually bound (null until started), or why it couldn't start
+  bridgeStatus: { port: number | null; error: string | null };
+  interruptedTrades: InterruptedTradesMarker | null;
+  tradeCountdowns: Record<string, TradeCountdown>;
 
   // Trade History
   tradeHistory: TradeHistoryItem[];
@@ -74,6 +114,9 @@ interface AppStoreState {
   showVpnWarning: boolean;
   vpnWarningDismissed: boolean;
 
+  // Crash report from the previous session
+  crashReport: CrashReport | null;
+
   // Emergency Withdraw
   showWithdrawModal: boolean;
   withdrawDestination: string;
@@ -108,6 +151,10 @@ interface AppStoreState {
   setTvPosition: (position: TVPositionData | null) => void;
   setTvOverlayVisible: (visible: boolean) => void;
   setPendingExtensionTrade: (pending: boolean) => void;
+  setBridgeToken: (token: string) => void;
+  setBridgeStatus: (status: { port: number | null; error: string | null }) => void;
+  setInterruptedTrades: (marker: InterruptedTradesMarker | null) => void;
+  updateTradeCountdown: (tradeId: string, countdown: TradeCountdown | null) => void;
 
   setTradeHistory: (historyOrUpdater: TradeHistoryItem[] | ((prev: TradeHistoryItem[]) => TradeHistoryItem[])) => void;
   addTradeToHistory: (trade: TradeHistoryItem) => void;
@@ -119,6 +166,8 @@ interface AppStoreState {
   setShowVpnWarning: (show: boolean) => void;
   setVpnWarningDismissed: (dismissed: boolean) => void;
 
+  setCrashReport: (report: CrashReport | null) => void;
+
   setShowWithdrawModal: (show: boolean) => void;
   setWithdrawDestination: (destination: string) => void;
   setWithdrawing: (withdrawing: boolean) => void;
@@ -157,6 +206,10 @@ export const useAppStore = create<AppStoreState>((set) => ({
   tvPosition: null,
   tvOverlayVisible: false,
   pendingExtensionTrade: false,
+  bridgeToken: "",
+  bridgeStatus: { port: null, error: null },
+  interruptedTrades: null,
+  tradeCountdowns: {},
 
   tradeHistory: [],
 
@@ -166,6 +219,8 @@ export const useAppStore = create<AppStoreState>((set) => ({
   showVpnWarning: false,
   vpnWarningDismissed: false,
 
+  crashReport: null,
+
   showWithdrawModal: false,
   withdrawDestination: "",
   withdrawing: false,
@@ -199,6 +254,18 @@ export const useAppStore = create<AppStoreState>((set) => ({
   setTvPosition: (tvPosition) => set({ tvPosition }),
   setTvOverlayVisible: (tvOverlayVisible) => set({ tvOverlayVisible }),
   setPendingExtensionTrade: (pendingExtensionTrade) => set({ pendingExtensionTrade }),
+  setBridgeToken: (bridgeToken) => set({ bridgeToken }),
+  setBridgeStatus: (bridgeStatus) => set({ bridgeStatus }),
+  setInterruptedTrades: (interruptedTrades) => set({ interruptedTrades }),
+  updateTradeCountdown: (tradeId, countdown) => set((state) => {
+    const tradeCountdowns = { ...state.tradeCountdowns };
+    if (countdown) {
+      tradeCountdowns[tradeId] = countdown;
+    } else {
+      delete tradeCountdowns[tradeId];
+    }
+    return { tradeCountdowns };
+  }),
 
   setTradeHistory: (historyOrUpdater) => set((state) => ({
     tradeHistory: typeof historyOrUpdater === 'function'
@@ -220,6 +287,8 @@ export const useAppStore = create<AppStoreState>((set) => ({
   setShowVpnWarning: (showVpnWarning) => set({ showVpnWarning }),
   setVpnWarningDismissed: (vpnWarningDismissed) => set({ vpnWarningDismissed }),
 
+  setCrashReport: (crashReport) => set({ crashReport }),
+
   setShowWithdrawModal: (showWithdrawModal) => set({ showWithdrawModal }),
   setWithdrawDestination: (withdrawDestination) => set({ withdrawDestination }),
   setWithdrawing: (withdrawing) => set({ withdrawing }),
@@ -238,4 +307,4 @@ export const useAppStore = create<AppStoreState>((set) => ({
 }));
 
 // Re-export types
-export type { TVPositionData, TradeHistoryItem };
+export type { TVPositionData, TradeHistoryItem, InterruptedTradesMarker, TradeCountdown, CrashReport };
@@ -26,6 +26,8 @@ interface SettingsState {
   // TradingView Bridge settings
   extensionSkipConfirm: boolean;
   extensionEnabled: boolean;
+  countdownSecs: number;
+  bridgePort: number;
 
   // Google Sheets
   googleSheetsUrl: string;
@@ -49,6 +51,8 @@ interface SettingsState {
   setPnlTolerance: (tolerance: number) => void;
   setExtensionSkipConfirm: (enabled: boolean) => void;
   setExtensionEnabled: (enabled: boolean) => void;
+  setCountdownSecs: (secs: number) => void;
+  setBridgePort: (port: number) => void;
   setGoogleSheetsUrl: (url: string) => void;
   setSettingsLoaded: (loaded: boolean) => void;
 
@@ -73,6 +77,8 @@ export const useSettingsStore = create<SettingsState>((set) => ({
   pnlTolerance: 0.10,
   extensionSkipConfirm: true,
   extensionEnabled: true,
+  countdownSecs: 0,
+  bridgePort: 3456,
   googleSheetsUrl: "",
   settingsLoaded: false,
 
@@ -92,6 +98,8 @@ export const useSettingsStore = create<SettingsState>((set) => ({
   setPnlTolerance: (pnlTolerance) => set({ pnlTolerance }),
   setExtensionSkipConfirm: (extensionSkipConfirm) => set({ extensionSkipConfirm }),
   setExtensionEnabled: (extensionEnabled) => set({ extensionEnabled }),
+  setCountdownSecs: (countdownSecs) => set({ countdownSecs }),
+  setBridgePort: (bridgePort) => set({ bridgePort }),
   setGoogleSheetsUrl: (googleSheetsUrl) => set({ googleSheetsUrl }),
   setSettingsLoaded: (settingsLoaded) => set({ settingsLoaded }),
 